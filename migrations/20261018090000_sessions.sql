@@ -0,0 +1,12 @@
+-- sessions back the refresh tokens handed out at login. access tokens carry the
+-- session id so that revoking a session also invalidates its access tokens.
+create table sessions (
+    session_id uuid primary key,
+    user_id integer not null references users(user_id) on delete cascade,
+    refresh_token_hash text not null unique,
+    expires_at timestamptz not null,
+    revoked_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index sessions_user_id_idx on sessions(user_id);
@@ -0,0 +1,10 @@
+-- single use tokens for resetting a forgotten password. only the hash of the token is stored.
+create table password_resets (
+    token_hash text primary key,
+    user_id integer not null references users(user_id) on delete cascade,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index password_resets_user_id_idx on password_resets(user_id);
@@ -0,0 +1,12 @@
+-- pending email changes. the new email is only applied once the user confirms it with the
+-- single use token that is sent to the new address.
+create table email_changes (
+    token_hash text primary key,
+    user_id integer not null references users(user_id) on delete cascade,
+    email varchar(256) not null,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index email_changes_user_id_idx on email_changes(user_id);
@@ -0,0 +1,15 @@
+-- track when the email of a user was verified
+alter table users add column email_verified_at timestamptz;
+
+-- single use tokens that are emailed to a user to verify their email. the email is stored so
+-- a token sent before an email change can't verify the new email.
+create table email_verifications (
+    token_hash text primary key,
+    user_id integer not null references users(user_id) on delete cascade,
+    email varchar(256) not null,
+    expires_at timestamptz not null,
+    used_at timestamptz,
+    created_at timestamptz not null default now()
+);
+
+create index email_verifications_user_id_idx on email_verifications(user_id);
@@ -0,0 +1,5 @@
+-- deleting a user deletes the scoreboards they created along with their teams
+alter table scoreboards
+    drop constraint scoreboards_created_by_fkey,
+    add constraint scoreboards_created_by_fkey
+        foreign key (created_by) references users(user_id) on delete cascade;
@@ -0,0 +1,21 @@
+-- roles a user can have on a scoreboard
+create type scoreboard_role as enum ('owner', 'editor', 'viewer');
+
+-- the users that can access a scoreboard and what they are allowed to do with it
+create table scoreboard_members (
+    scoreboard_id integer not null references scoreboards(scoreboard_id) on delete cascade,
+    user_id integer not null references users(user_id) on delete cascade,
+    role scoreboard_role not null,
+    created_at timestamptz not null default now(),
+    primary key (scoreboard_id, user_id)
+);
+
+create index scoreboard_members_user_id_idx on scoreboard_members(user_id);
+
+-- the creator of every existing scoreboard is its owner
+insert into
+    scoreboard_members (scoreboard_id, user_id, role)
+select
+    scoreboard_id, created_by, 'owner'
+from
+    scoreboards;
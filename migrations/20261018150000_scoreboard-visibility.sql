@@ -0,0 +1,8 @@
+-- who can see a scoreboard. private boards are only visible to members, unlisted boards are
+-- visible to anyone with the id and public boards are also listed on the profile of the creator.
+create type scoreboard_visibility as enum ('private', 'unlisted', 'public');
+
+-- every existing scoreboard was visible to everyone so they stay public, new scoreboards
+-- are private unless asked otherwise
+alter table scoreboards add column visibility scoreboard_visibility not null default 'public';
+alter table scoreboards alter column visibility set default 'private';
@@ -0,0 +1,23 @@
+-- notify listeners on the scoreboard_events channel whenever the name or score of a team
+-- changes so that every replica can push the change to its connected clients
+CREATE OR REPLACE FUNCTION notify_team_updated()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.name IS DISTINCT FROM OLD.name OR NEW.score IS DISTINCT FROM OLD.score THEN
+        PERFORM pg_notify(
+            'scoreboard_events',
+            json_build_object(
+                'scoreboard_id', NEW.scoreboard_id,
+                'type', 'team.updated',
+                'data', row_to_json(NEW)
+            )::text
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_team_updated
+AFTER UPDATE ON teams
+FOR EACH ROW
+EXECUTE PROCEDURE notify_team_updated();
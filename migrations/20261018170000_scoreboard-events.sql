@@ -0,0 +1,75 @@
+-- every change to a scoreboard is recorded as an event so clients that reconnect can catch up
+-- on what they missed. events are kept after the scoreboard is deleted so the deletion itself
+-- can be replayed.
+create table scoreboard_events (
+    event_id bigserial primary key,
+    scoreboard_id integer not null,
+    event_type text not null,
+    data jsonb not null,
+    created_at timestamptz not null default now()
+);
+
+create index scoreboard_events_scoreboard_id_idx on scoreboard_events(scoreboard_id, event_id);
+
+-- the event is published on the scoreboard_events channel after it is recorded
+CREATE OR REPLACE FUNCTION record_scoreboard_event(scoreboard_id integer, event_type text, data jsonb)
+RETURNS VOID AS $$
+DECLARE
+    event scoreboard_events;
+BEGIN
+    INSERT INTO
+        scoreboard_events (scoreboard_id, event_type, data)
+    VALUES
+        (scoreboard_id, event_type, data)
+    RETURNING
+        * INTO event;
+
+    PERFORM pg_notify('scoreboard_events', row_to_json(event)::text);
+END;
+$$ LANGUAGE plpgsql;
+
+-- replace the notify only trigger with one that records the event
+DROP TRIGGER notify_team_updated ON teams;
+DROP FUNCTION notify_team_updated();
+
+CREATE OR REPLACE FUNCTION record_team_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'team.created', to_jsonb(NEW));
+    ELSIF NEW.name IS DISTINCT FROM OLD.name OR NEW.score IS DISTINCT FROM OLD.score THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'team.updated', to_jsonb(NEW));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_team_event
+AFTER INSERT OR UPDATE ON teams
+FOR EACH ROW
+EXECUTE PROCEDURE record_team_event();
+
+CREATE OR REPLACE FUNCTION record_scoreboard_change_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM record_scoreboard_event(
+            OLD.scoreboard_id,
+            'scoreboard.deleted',
+            jsonb_build_object('scoreboard_id', OLD.scoreboard_id)
+        );
+        RETURN OLD;
+    END IF;
+
+    -- updated_at is bumped whenever a team changes, that is already covered by the team events
+    IF (to_jsonb(NEW) - 'updated_at') IS DISTINCT FROM (to_jsonb(OLD) - 'updated_at') THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'scoreboard.updated', to_jsonb(NEW));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_scoreboard_change_event
+AFTER UPDATE OR DELETE ON scoreboards
+FOR EACH ROW
+EXECUTE PROCEDURE record_scoreboard_change_event();
@@ -0,0 +1,13 @@
+-- every change to the score of a team, used for the history and to undo and redo changes
+create table score_events (
+    score_event_id serial primary key,
+    scoreboard_id integer not null references scoreboards(scoreboard_id) on delete cascade,
+    team_id integer not null references teams(team_id) on delete cascade,
+    delta integer not null,
+    score integer not null,
+    created_by integer references users(user_id) on delete set null,
+    created_at timestamptz not null default now(),
+    undone_at timestamptz
+);
+
+create index score_events_scoreboard_id_idx on score_events(scoreboard_id, score_event_id);
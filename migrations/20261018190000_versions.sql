@@ -0,0 +1,44 @@
+-- versions are used for etags and to detect concurrent changes
+alter table scoreboards add version integer not null default 1;
+alter table teams add version integer not null default 1;
+
+-- bumps the version on every update. changing a team also updates its scoreboard, so the version
+-- of a scoreboard changes whenever one of its teams does.
+CREATE OR REPLACE FUNCTION trigger_increment_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version = OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER increment_version
+BEFORE UPDATE ON scoreboards
+FOR EACH ROW
+EXECUTE PROCEDURE trigger_increment_version();
+
+CREATE TRIGGER increment_version
+BEFORE UPDATE ON teams
+FOR EACH ROW
+EXECUTE PROCEDURE trigger_increment_version();
+
+-- the version changes along with updated_at, so it is ignored when looking for scoreboard changes
+CREATE OR REPLACE FUNCTION record_scoreboard_change_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM record_scoreboard_event(
+            OLD.scoreboard_id,
+            'scoreboard.deleted',
+            jsonb_build_object('scoreboard_id', OLD.scoreboard_id)
+        );
+        RETURN OLD;
+    END IF;
+
+    -- updated_at is bumped whenever a team changes, that is already covered by the team events
+    IF (to_jsonb(NEW) - 'updated_at' - 'version') IS DISTINCT FROM (to_jsonb(OLD) - 'updated_at' - 'version') THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'scoreboard.updated', to_jsonb(NEW));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
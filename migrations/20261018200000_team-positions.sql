@@ -0,0 +1,63 @@
+-- the order the teams of a scoreboard are shown in
+alter table teams add position integer not null default 0;
+
+update
+    teams t
+set
+    position = p.position
+from
+    (
+        select
+            team_id, row_number() over (partition by scoreboard_id order by team_id) - 1 as position
+        from
+            teams
+    ) p
+where
+    t.team_id = p.team_id;
+
+-- adding or removing a team changes the scoreboard just like updating one does
+CREATE OR REPLACE FUNCTION trigger_set_timestamp_scoreboard_of_team()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE
+        scoreboards
+    SET
+        updated_at = now()
+    WHERE
+        scoreboard_id = COALESCE(NEW.scoreboard_id, OLD.scoreboard_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_timestamp_scoreboard_of_team
+AFTER INSERT OR DELETE ON teams
+FOR EACH ROW
+EXECUTE PROCEDURE trigger_set_timestamp_scoreboard_of_team();
+
+-- removed teams are published as well, unless the whole scoreboard was deleted. moving a team
+-- is an update of the team.
+CREATE OR REPLACE FUNCTION record_team_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'team.created', to_jsonb(NEW));
+    ELSIF TG_OP = 'DELETE' THEN
+        IF EXISTS (SELECT 1 FROM scoreboards WHERE scoreboard_id = OLD.scoreboard_id) THEN
+            PERFORM record_scoreboard_event(OLD.scoreboard_id, 'team.deleted', to_jsonb(OLD));
+        END IF;
+        RETURN OLD;
+    ELSIF NEW.name IS DISTINCT FROM OLD.name
+        OR NEW.score IS DISTINCT FROM OLD.score
+        OR NEW.position IS DISTINCT FROM OLD.position THEN
+        PERFORM record_scoreboard_event(NEW.scoreboard_id, 'team.updated', to_jsonb(NEW));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER record_team_event ON teams;
+
+CREATE TRIGGER record_team_event
+AFTER INSERT OR UPDATE OR DELETE ON teams
+FOR EACH ROW
+EXECUTE PROCEDURE record_team_event();
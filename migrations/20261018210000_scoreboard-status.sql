@@ -0,0 +1,15 @@
+-- active scoreboards are being played, finished ones have their scores frozen and archived ones
+-- are put away
+create type scoreboard_status as enum ('active', 'finished', 'archived');
+
+-- decides which teams win when a scoreboard is finished
+create type scoreboard_win_condition as enum ('highest', 'lowest');
+
+alter table scoreboards add column status scoreboard_status not null default 'active';
+alter table scoreboards add column win_condition scoreboard_win_condition not null default 'highest';
+alter table scoreboards add column finished_at timestamptz;
+
+-- the teams that won a finished scoreboard
+alter table teams add column is_winner boolean not null default false;
+
+create index scoreboards_status_idx on scoreboards(status);
@@ -0,0 +1,10 @@
+-- the rules scores on a scoreboard have to follow. a scoreboard finishes by itself once a team
+-- reaches the target score.
+alter table scoreboards add column target_score integer;
+alter table scoreboards add column min_score integer;
+alter table scoreboards add column max_score integer;
+alter table scoreboards add column allow_negative boolean not null default true;
+alter table scoreboards add column score_step integer not null default 1;
+
+alter table scoreboards add constraint scoreboards_score_step_check check (score_step > 0);
+alter table scoreboards add constraint scoreboards_score_range_check check (min_score <= max_score);
@@ -0,0 +1,32 @@
+-- scoreboards can be scored round by round. the total score of a team is the sum of its round
+-- scores once a scoreboard has rounds.
+create type round_status as enum ('open', 'closed');
+
+create table rounds (
+    round_id serial primary key,
+    scoreboard_id integer not null references scoreboards(scoreboard_id) on delete cascade,
+    number integer not null,
+    status round_status not null default 'open',
+    opened_at timestamptz not null default now(),
+    closed_at timestamptz,
+    unique (scoreboard_id, number)
+);
+
+-- only one round of a scoreboard can be open at a time
+create unique index rounds_open_idx on rounds(scoreboard_id) where status = 'open';
+
+create table round_scores (
+    round_id integer not null references rounds(round_id) on delete cascade,
+    team_id integer not null references teams(team_id) on delete cascade,
+    score integer not null default 0,
+    primary key (round_id, team_id)
+);
+
+-- undoing a change that was made in a round also changes the score of the round
+alter table score_events add column round_id integer references rounds(round_id) on delete set null;
+
+-- opening or closing a round changes the scoreboard, the scores of a round change it through its teams
+CREATE TRIGGER set_timestamp_scoreboard_of_round
+AFTER INSERT OR UPDATE OR DELETE ON rounds
+FOR EACH ROW
+EXECUTE PROCEDURE trigger_set_timestamp_scoreboard_of_team();
@@ -0,0 +1,51 @@
+-- the players on a team, either users or guests that only have a name
+create table team_members (
+    team_member_id serial primary key,
+    team_id integer not null references teams(team_id) on delete cascade,
+    user_id integer references users(user_id) on delete cascade,
+    guest_name text,
+    created_at timestamptz not null default now(),
+    check ((user_id is null) <> (guest_name is null)),
+    unique (team_id, user_id)
+);
+
+create index team_members_user_id_idx on team_members(user_id);
+
+-- adding or removing a player changes the team, which also changes its scoreboard. nothing has to
+-- change when the team itself is being deleted.
+CREATE OR REPLACE FUNCTION record_team_member_event()
+RETURNS TRIGGER AS $$
+DECLARE
+    scoreboard integer;
+BEGIN
+    SELECT
+        scoreboard_id INTO scoreboard
+    FROM
+        teams
+    WHERE
+        team_id = COALESCE(NEW.team_id, OLD.team_id);
+
+    IF scoreboard IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    UPDATE
+        teams
+    SET
+        version = version
+    WHERE
+        team_id = COALESCE(NEW.team_id, OLD.team_id);
+
+    IF TG_OP = 'INSERT' THEN
+        PERFORM record_scoreboard_event(scoreboard, 'team_member.added', to_jsonb(NEW));
+    ELSE
+        PERFORM record_scoreboard_event(scoreboard, 'team_member.removed', to_jsonb(OLD));
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_team_member_event
+AFTER INSERT OR DELETE ON team_members
+FOR EACH ROW
+EXECUTE PROCEDURE record_team_member_event();
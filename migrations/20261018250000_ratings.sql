@@ -0,0 +1,27 @@
+-- the skill rating of a user in a game
+create table ratings (
+    user_id integer not null references users(user_id) on delete cascade,
+    game text not null,
+    rating double precision not null,
+    games_rated integer not null default 0,
+    updated_at timestamptz not null default now(),
+    primary key (user_id, game)
+);
+
+create index ratings_game_rating_idx on ratings(game, rating desc);
+
+-- every change to a rating along with the scoreboard that caused it
+create table rating_history (
+    rating_history_id serial primary key,
+    user_id integer not null references users(user_id) on delete cascade,
+    game text not null,
+    scoreboard_id integer references scoreboards(scoreboard_id) on delete set null,
+    rating_before double precision not null,
+    rating_after double precision not null,
+    created_at timestamptz not null default now()
+);
+
+create index rating_history_user_id_idx on rating_history(user_id, game);
+
+-- a scoreboard only changes the ratings of its players once
+alter table scoreboards add rated_at timestamptz;
@@ -0,0 +1,107 @@
+-- the catalog of games. aliases are other names of a game in lower case, so free text games can be
+-- matched case insensitively. the scoring settings are the defaults for new scoreboards.
+create table games (
+    game_id serial primary key,
+    name text not null,
+    aliases text[] not null default '{}',
+    win_condition scoreboard_win_condition not null default 'highest',
+    target_score integer,
+    min_score integer,
+    max_score integer,
+    allow_negative boolean not null default true,
+    score_step integer not null default 1,
+    min_teams integer not null default 1,
+    max_teams integer,
+    created_at timestamptz not null default now(),
+    check (min_teams >= 1 and (max_teams is null or max_teams >= min_teams))
+);
+
+create unique index games_name_idx on games(lower(name));
+create index games_aliases_idx on games using gin(aliases);
+
+insert into
+    games (name, aliases, win_condition, target_score, allow_negative, min_teams, max_teams)
+values
+    ('Catan', '{"settlers of catan", "the settlers of catan", "settlers"}', 'highest', 10, false, 3, 6),
+    ('Carcassonne', '{}', 'highest', null, false, 2, 6),
+    ('Ticket to Ride', '{"ttr"}', 'highest', null, true, 2, 5),
+    ('Scrabble', '{}', 'highest', null, false, 2, 4),
+    ('Yahtzee', '{}', 'highest', null, false, 1, null),
+    ('Uno', '{}', 'highest', 500, false, 2, 10),
+    ('Hearts', '{}', 'lowest', 100, false, 4, 4),
+    ('Golf', '{"mini golf", "minigolf", "disc golf"}', 'lowest', null, false, 1, null),
+    ('Darts', '{"501"}', 'lowest', null, false, 2, null),
+    ('Cornhole', '{"bags"}', 'highest', 21, false, 2, 2);
+
+-- games that are not in the catalog yet are added with their most common spelling
+insert into
+    games (name)
+select distinct on (lower(trim(s.game)))
+    trim(s.game)
+from
+    scoreboards s
+where
+    trim(s.game) <> ''
+and
+    not exists (
+        select 1 from games g where lower(g.name) = lower(trim(s.game)) or lower(trim(s.game)) = any(g.aliases)
+    )
+group by
+    trim(s.game)
+order by
+    lower(trim(s.game)), count(*) desc, trim(s.game);
+
+-- scoreboards point at their game in the catalog and use its name, so they can be aggregated
+alter table scoreboards add game_id integer references games(game_id) on delete set null;
+
+create index scoreboards_game_id_idx on scoreboards(game_id);
+
+update
+    scoreboards s
+set
+    game_id = g.game_id,
+    game = g.name
+from
+    games g
+where
+    lower(g.name) = lower(trim(s.game)) or lower(trim(s.game)) = any(g.aliases);
+
+-- the ratings follow the names of the games. a user that was rated under several spellings of a
+-- game keeps the rating they played the most games with.
+update
+    rating_history h
+set
+    game = s.game
+from
+    scoreboards s
+where
+    h.scoreboard_id = s.scoreboard_id and h.game <> s.game;
+
+delete from
+    ratings r
+using
+    games g
+where
+    (lower(g.name) = lower(trim(r.game)) or lower(trim(r.game)) = any(g.aliases))
+and
+    exists (
+        select
+            1
+        from
+            ratings o
+        where
+            o.user_id = r.user_id
+        and
+            (lower(g.name) = lower(trim(o.game)) or lower(trim(o.game)) = any(g.aliases))
+        and
+            (o.games_rated > r.games_rated or (o.games_rated = r.games_rated and o.game < r.game))
+    );
+
+update
+    ratings r
+set
+    game = g.name
+from
+    games g
+where
+    (lower(g.name) = lower(trim(r.game)) or lower(trim(r.game)) = any(g.aliases)) and r.game <> g.name;
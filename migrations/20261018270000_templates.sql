@@ -0,0 +1,19 @@
+-- saved settings and teams that users create the scoreboards they play again and again from
+create table scoreboard_templates (
+    template_id serial primary key,
+    created_by integer not null references users(user_id) on delete cascade,
+    name text not null,
+    game text not null,
+    game_id integer references games(game_id) on delete set null,
+    visibility scoreboard_visibility not null default 'private',
+    win_condition scoreboard_win_condition not null default 'highest',
+    target_score integer,
+    min_score integer,
+    max_score integer,
+    allow_negative boolean not null default true,
+    score_step integer not null default 1,
+    teams text[] not null default '{}',
+    created_at timestamptz not null default now()
+);
+
+create index scoreboard_templates_created_by_idx on scoreboard_templates(created_by);
@@ -0,0 +1,57 @@
+create type tournament_format as enum ('single_elimination', 'double_elimination', 'round_robin');
+create type tournament_status as enum ('active', 'finished');
+create type tournament_bracket as enum ('winners', 'losers', 'grand_final', 'round_robin');
+create type tournament_match_status as enum ('pending', 'ready', 'completed', 'void');
+
+-- a tournament where every match is played on its own scoreboard
+create table tournaments (
+    tournament_id serial primary key,
+    created_by integer not null references users(user_id) on delete cascade,
+    name text not null,
+    game text not null,
+    game_id integer references games(game_id) on delete set null,
+    format tournament_format not null,
+    status tournament_status not null default 'active',
+    winner_entrant_id integer,
+    created_at timestamptz not null default now()
+);
+
+-- the teams or players taking part in a tournament, seeded from 1
+create table tournament_entrants (
+    entrant_id serial primary key,
+    tournament_id integer not null references tournaments(tournament_id) on delete cascade,
+    name text not null,
+    seed integer not null,
+    unique (tournament_id, seed)
+);
+
+alter table tournaments add foreign key (winner_entrant_id) references tournament_entrants(entrant_id) on delete set null;
+
+-- the matches of a tournament. a void slot never gets an entrant, because of a bye earlier in the
+-- bracket. the winner and the loser of a match move on to the slots of the next matches.
+create table tournament_matches (
+    match_id serial primary key,
+    tournament_id integer not null references tournaments(tournament_id) on delete cascade,
+    bracket tournament_bracket not null,
+    round integer not null,
+    position integer not null,
+    entrant1_id integer references tournament_entrants(entrant_id) on delete cascade,
+    entrant2_id integer references tournament_entrants(entrant_id) on delete cascade,
+    slot1_void boolean not null default false,
+    slot2_void boolean not null default false,
+    scoreboard_id integer references scoreboards(scoreboard_id) on delete set null,
+    team1_id integer references teams(team_id) on delete set null,
+    team2_id integer references teams(team_id) on delete set null,
+    status tournament_match_status not null default 'pending',
+    winner_entrant_id integer references tournament_entrants(entrant_id) on delete cascade,
+    score1 integer,
+    score2 integer,
+    winner_match_id integer references tournament_matches(match_id) on delete set null,
+    winner_slot integer,
+    loser_match_id integer references tournament_matches(match_id) on delete set null,
+    loser_slot integer,
+    completed_at timestamptz,
+    unique (tournament_id, bracket, round, position)
+);
+
+create index tournament_matches_scoreboard_id_idx on tournament_matches(scoreboard_id);
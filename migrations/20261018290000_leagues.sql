@@ -0,0 +1,35 @@
+-- a group of users that plays seasons of games together
+create table leagues (
+    league_id serial primary key,
+    created_by integer not null references users(user_id) on delete cascade,
+    name text not null,
+    created_at timestamptz not null default now()
+);
+
+-- the users taking part in a league, including its creator
+create table league_members (
+    league_id integer not null references leagues(league_id) on delete cascade,
+    user_id integer not null references users(user_id) on delete cascade,
+    created_at timestamptz not null default now(),
+    primary key (league_id, user_id)
+);
+
+create index league_members_user_id_idx on league_members(user_id);
+
+-- a season of a league along with the points a win, a draw and a loss are worth in its standings
+create table seasons (
+    season_id serial primary key,
+    league_id integer not null references leagues(league_id) on delete cascade,
+    name text not null,
+    points_for_win integer not null default 3,
+    points_for_draw integer not null default 1,
+    points_for_loss integer not null default 0,
+    created_at timestamptz not null default now()
+);
+
+create index seasons_league_id_idx on seasons(league_id);
+
+-- the finished scoreboards of a season make up its standings
+alter table scoreboards add season_id integer references seasons(season_id) on delete set null;
+
+create index scoreboards_season_id_idx on scoreboards(season_id);
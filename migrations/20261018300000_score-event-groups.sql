@@ -0,0 +1,10 @@
+-- the score changes that were made together, like the teams of a batch or the scores of a round,
+-- share a group and are undone and redone together
+alter table score_events add column group_id uuid;
+
+-- every change that was made before gets a group of its own
+update score_events set group_id = md5(score_event_id::text)::uuid;
+
+alter table score_events alter column group_id set not null;
+
+create index score_events_group_id_idx on score_events(group_id);
@@ -6,7 +6,26 @@ use serde::Deserialize;
 pub struct Config {
     pub database_url: String,
     pub jwt_secret: String,
+    /// global salt that passwords used to be hashed with, only used to detect legacy hashes
     pub salt: String,
+    #[serde(default = "default_argon2_memory_cost")]
+    pub argon2_memory_cost: u32,
+    #[serde(default = "default_argon2_iterations")]
+    pub argon2_iterations: u32,
+    #[serde(default = "default_argon2_variant")]
+    pub argon2_variant: String,
+    /// blocks users from creating scoreboards until their email is verified
+    #[serde(default)]
+    pub require_verified_email: bool,
+    #[serde(default = "default_app_url")]
+    pub app_url: String,
+    #[serde(default = "default_mail_from")]
+    pub mail_from: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub mail_outbox_dir: Option<String>,
 }
 
 impl Config {
@@ -22,3 +41,28 @@ impl Config {
             .expect("Failed to load configuration from environment")
     }
 }
+
+/// The amount of memory in KiB argon2 uses to hash a password
+fn default_argon2_memory_cost() -> u32 {
+    19456
+}
+
+/// The number of passes argon2 makes over the memory
+fn default_argon2_iterations() -> u32 {
+    2
+}
+
+/// The argon2 variant, one of argon2i, argon2d or argon2id
+fn default_argon2_variant() -> String {
+    String::from("argon2id")
+}
+
+/// The url of the app that links in emails point to
+fn default_app_url() -> String {
+    String::from("https://tallii.io")
+}
+
+/// The address that emails are sent from
+fn default_mail_from() -> String {
+    String::from("Tallii <noreply@tallii.io>")
+}
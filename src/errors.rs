@@ -32,9 +32,15 @@ pub enum TalliiError {
     #[error("not allowed to perform this action")]
     Forbidden,
 
+    #[error("not found")]
+    NotFound,
+
     #[error("user email taken")]
     UserEmailTaken,
 
+    #[error("user email not verified")]
+    UserEmailNotVerified,
+
     #[error("missing bearer token")]
     MissingBearerToken,
 
@@ -43,6 +49,12 @@ pub enum TalliiError {
 
     #[error("validation error: {0}")]
     ValidationError(String),
+
+    #[error("the resource was changed in the meantime")]
+    PreconditionFailed,
+
+    #[error("the scoreboard is no longer active")]
+    ScoreboardNotActive,
 }
 
 /// Required in order for warp to accept the TalliiError as a valid rejection
@@ -62,6 +74,10 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
         status_code = StatusCode::BAD_REQUEST;
         message = format!("{:?}", error);
         code = String::from("INVALID_REQUEST_BODY");
+    } else if let Some(error) = err.find::<warp::reject::InvalidQuery>() {
+        status_code = StatusCode::BAD_REQUEST;
+        message = format!("{:?}", error);
+        code = String::from("INVALID_QUERY");
     } else if let Some(e) = err.find::<TalliiError>() {
         match e {
             TalliiError::DatabaseError(error) => {
@@ -89,6 +105,11 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
                 message = "the provide email has been taken.".to_string();
                 code = String::from("USER_EMAIL_TAKEN");
             }
+            TalliiError::UserEmailNotVerified => {
+                status_code = StatusCode::FORBIDDEN;
+                message = "the email of the user has not been verified.".to_string();
+                code = String::from("USER_EMAIL_NOT_VERIFIED");
+            }
             TalliiError::Unauthorized => {
                 status_code = StatusCode::UNAUTHORIZED;
                 message = "the provided credentials are invalid.".to_string();
@@ -109,13 +130,28 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
                 message = "not allowed to perform this action.".to_string();
                 code = String::from("FORBIDDEN");
             }
+            TalliiError::NotFound => {
+                status_code = StatusCode::NOT_FOUND;
+                message = String::from("Not Found");
+                code = String::from("NOT_FOUND");
+            }
+            TalliiError::PreconditionFailed => {
+                status_code = StatusCode::PRECONDITION_FAILED;
+                message = "the resource was changed in the meantime.".to_string();
+                code = String::from("PRECONDITION_FAILED");
+            }
+            TalliiError::ScoreboardNotActive => {
+                status_code = StatusCode::CONFLICT;
+                message = "the scoreboard is no longer active.".to_string();
+                code = String::from("SCOREBOARD_NOT_ACTIVE");
+            }
             TalliiError::SQLXError => {
                 status_code = StatusCode::INTERNAL_SERVER_ERROR;
                 message = "something went wrong with the database".to_string();
                 code = String::from("INTERNAL_SERVER_ERROR");
             }
         }
-    } else if let Some(_) = err.find::<warp::reject::MethodNotAllowed>() {
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
         status_code = StatusCode::METHOD_NOT_ALLOWED;
         message = String::from("Method Not Allowed");
         code = String::from("INTERNAL_SERVER_ERROR");
@@ -0,0 +1,44 @@
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// Formats the version of a resource as a strong etag
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Checks the If-Match header against the current version of a resource. Requests without the
+/// header always match.
+pub fn check_if_match(if_match: &Option<String>, version: i32) -> Result<()> {
+    match if_match {
+        Some(header) if !header_matches(header, version, false) => {
+            Err(TalliiError::PreconditionFailed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks if the If-None-Match header contains the current version of a resource, in which case
+/// the client already has it
+pub fn is_not_modified(if_none_match: &Option<String>, version: i32) -> bool {
+    match if_none_match {
+        Some(header) => header_matches(header, version, true),
+        None => false,
+    }
+}
+
+/// Checks if any of the etags in the header match the version. If-Match only allows strong
+/// etags to match while If-None-Match allows weak ones as well.
+fn header_matches(header: &str, version: i32, allow_weak: bool) -> bool {
+    let current = etag(version);
+
+    header.split(',').map(str::trim).any(|tag| {
+        if tag == "*" {
+            return true;
+        }
+
+        match tag.strip_prefix("W/") {
+            Some(weak) => allow_weak && weak == current,
+            None => tag == current,
+        }
+    })
+}
@@ -0,0 +1,111 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::errors::TalliiError;
+use crate::scoreboards::db::WinCondition;
+use crate::Result;
+
+/// A game of the catalog along with the default settings for its scoreboards
+#[derive(FromRow, Serialize, Debug)]
+pub struct Game {
+    pub game_id: i32,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub win_condition: WinCondition,
+    pub target_score: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub allow_negative: bool,
+    pub score_step: i32,
+    pub min_teams: i32,
+    pub max_teams: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl Game {
+    /// checks if a scoreboard of the game can have that many teams
+    pub fn allows_teams(&self, teams: usize) -> bool {
+        let teams = teams as i32;
+
+        teams >= self.min_teams && self.max_teams.is_none_or(|max_teams| teams <= max_teams)
+    }
+
+    /// fetches the games whose name or one of its aliases matches the query, or every game when
+    /// there is no query
+    pub async fn search_games(conn: &PgPool, query: &Option<String>) -> Result<Vec<Game>> {
+        // wildcards in the query match themselves
+        let like_term = query.as_ref().map(|query| {
+            let escaped = query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+
+            format!("%{}%", escaped)
+        });
+
+        sqlx::query_as::<_, Game>(
+            r#"
+                select
+                    *
+                from
+                    games
+                where
+                    $1::text is null
+                or
+                    name ilike $1 escape '\'
+                or
+                    exists (
+                        select 1 from unnest(aliases) a where a ilike $1 escape '\'
+                    )
+                order by
+                    name
+            "#,
+        )
+        .bind(&like_term)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a single game
+    pub async fn get_game(conn: &PgPool, game_id: &i32) -> Result<Option<Game>> {
+        sqlx::query_as::<_, Game>(
+            r#"
+                select
+                    *
+                from
+                    games
+                where
+                    game_id = $1
+            "#,
+        )
+        .bind(game_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// finds the game with the name or alias, ignoring case and surrounding whitespace
+    pub async fn find_game_by_name(conn: &PgPool, name: &str) -> Result<Option<Game>> {
+        sqlx::query_as::<_, Game>(
+            r#"
+                select
+                    *
+                from
+                    games
+                where
+                    lower(name) = lower(trim($1))
+                or
+                    lower(trim($1)) = any(aliases)
+                order by
+                    lower(name) = lower(trim($1)) desc
+                limit
+                    1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::Game;
+
+#[derive(Deserialize)]
+pub struct GamesQuery {
+    pub query: Option<String>,
+}
+
+/// gets the games of the catalog, optionally only the ones matching the query
+pub async fn get_games(
+    query: GamesQuery,
+    pool: Arc<PgPool>,
+    _token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let games = Game::search_games(&pool, &query.query).await?;
+
+    Ok(warp::reply::json(&games))
+}
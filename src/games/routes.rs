@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::wrappers::{with_auth, with_pool};
+
+use super::handlers::{self, GamesQuery};
+
+pub struct GameRoutes;
+
+impl GameRoutes {
+    /// Init the game routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        get_games(pool.clone())
+    }
+}
+
+/// GET /v1/games
+pub fn get_games(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "games")
+        .and(warp::get())
+        .and(warp::query::<GamesQuery>())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_games)
+}
@@ -0,0 +1,209 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::errors::TalliiError;
+use crate::teams::db::Team;
+use crate::Result;
+
+/// Representation of a change to the score of a team
+#[derive(FromRow, Serialize, Debug)]
+pub struct ScoreEvent {
+    pub score_event_id: i32,
+    pub scoreboard_id: i32,
+    pub team_id: i32,
+    pub delta: i32,
+    pub score: i32,
+    pub round_id: Option<i32>,
+    pub group_id: Uuid,
+    pub created_by: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+    pub undone_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+}
+
+impl ScoreEvent {
+    /// fetches the score history of a scoreboard, newest first
+    pub async fn get_score_events_by_scoreboard_id(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<ScoreEvent>> {
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                select
+                    *
+                from
+                    score_events
+                where
+                    scoreboard_id = $1
+                order by
+                    score_event_id desc
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the changes a user made to the scores of any scoreboard, oldest first
+    pub async fn get_score_events_by_user_id(
+        conn: &PgPool,
+        user_id: &i32,
+    ) -> Result<Vec<ScoreEvent>> {
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                select
+                    *
+                from
+                    score_events
+                where
+                    created_by = $1
+                order by
+                    score_event_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// records a change to the score of a team, along with the round it was made in if any.
+    /// changes that were made together share the group. a new change can't be redone after the
+    /// changes that were undone before it, so those are dropped.
+    pub async fn record_score_event_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        team: &Team,
+        delta: &i32,
+        round_id: Option<i32>,
+        group_id: &Uuid,
+        user_id: &i32,
+    ) -> Result<ScoreEvent> {
+        sqlx::query(
+            r#"
+                delete from
+                    score_events
+                where
+                    scoreboard_id = $1 and undone_at is not null
+            "#,
+        )
+        .bind(team.scoreboard_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                insert into
+                    score_events (scoreboard_id, team_id, delta, score, round_id, group_id, created_by)
+                values
+                    ($1, $2, $3, $4, $5, $6, $7)
+                returning
+                    *
+            "#,
+        )
+        .bind(team.scoreboard_id)
+        .bind(team.team_id)
+        .bind(delta)
+        .bind(team.score)
+        .bind(round_id)
+        .bind(group_id)
+        .bind(user_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the latest changes of a scoreboard that can be undone, which are the changes of
+    /// the group of the latest change that was not undone
+    pub async fn get_undoable_events_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<ScoreEvent>> {
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                select
+                    *
+                from
+                    score_events
+                where
+                    group_id = (
+                        select
+                            group_id
+                        from
+                            score_events
+                        where
+                            scoreboard_id = $1 and undone_at is null
+                        order by
+                            score_event_id desc
+                        limit 1
+                    )
+                order by
+                    score_event_id desc
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the changes of a scoreboard that were undone last. changes are undone from newest
+    /// to oldest so that is the group of the oldest undone change.
+    pub async fn get_redoable_events_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<ScoreEvent>> {
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                select
+                    *
+                from
+                    score_events
+                where
+                    group_id = (
+                        select
+                            group_id
+                        from
+                            score_events
+                        where
+                            scoreboard_id = $1 and undone_at is not null
+                        order by
+                            score_event_id asc
+                        limit 1
+                    )
+                order by
+                    score_event_id asc
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// marks the changes of a group as undone or as redone
+    pub async fn set_undone_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        group_id: &Uuid,
+        undone: bool,
+    ) -> Result<Vec<ScoreEvent>> {
+        sqlx::query_as::<_, ScoreEvent>(
+            r#"
+                update
+                    score_events
+                set
+                    undone_at = case when $1 then now() else null end
+                where
+                    group_id = $2
+                returning
+                    *
+            "#,
+        )
+        .bind(undone)
+        .bind(group_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
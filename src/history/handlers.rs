@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use jsonwebtoken::TokenData;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::TalliiError;
+use crate::members::db::Role;
+use crate::members::permissions::{require_role, require_view};
+use crate::rounds::db::Round;
+use crate::rounds::handlers::require_no_rounds_tx;
+use crate::scoreboards::db::Scoreboard;
+use crate::scoreboards::scoring::{finish_if_target_reached_tx, validate_score_change};
+use crate::teams::db::Team;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::ScoreEvent;
+
+#[derive(Serialize)]
+pub struct ScoreEventResponse {
+    pub score_events: Vec<ScoreEvent>,
+    pub teams: Vec<Team>,
+}
+
+/// gets the score history of a scoreboard
+pub async fn get_history(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // the history is visible to everyone that can see the scoreboard
+    require_view(&pool, &scoreboard_id, &token.claims.sub).await?;
+
+    let score_events = ScoreEvent::get_score_events_by_scoreboard_id(&pool, &scoreboard_id).await?;
+
+    Ok(warp::reply::json(&score_events))
+}
+
+/// reverts the latest change to the scores of a scoreboard along with the changes made with it
+pub async fn undo(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    apply_history(scoreboard_id, pool, token, true).await
+}
+
+/// reapplies the changes that were undone last
+pub async fn redo(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    apply_history(scoreboard_id, pool, token, false).await
+}
+
+/// undoes or redoes a group of changes and their scores in a single transaction
+async fn apply_history(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+    undo: bool,
+) -> ResponseResult<warp::reply::Json> {
+    // only users that can change the scores can undo them
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // the scores of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    let score_events = if undo {
+        ScoreEvent::get_undoable_events_tx(&mut tx, &scoreboard_id).await?
+    } else {
+        ScoreEvent::get_redoable_events_tx(&mut tx, &scoreboard_id).await?
+    };
+
+    let group_id = match score_events.first() {
+        Some(score_event) => score_event.group_id,
+        None => {
+            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+                if undo {
+                    "there is nothing to undo"
+                } else {
+                    "there is nothing to redo"
+                },
+            ))))
+        }
+    };
+
+    // changes made before the first round can't be reverted once the totals come from the rounds
+    if score_events.iter().any(|event| event.round_id.is_none()) {
+        require_no_rounds_tx(&mut tx, &scoreboard_id).await?;
+    }
+
+    // the scores of closed rounds are final, so only changes made in the open round can be reverted
+    if score_events.iter().any(|event| event.round_id.is_some()) {
+        let open_round_id = Round::get_open_round_tx(&mut tx, &scoreboard_id)
+            .await?
+            .map(|round| round.round_id);
+
+        if score_events
+            .iter()
+            .any(|event| event.round_id.is_some() && event.round_id != open_round_id)
+        {
+            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+                "the change was made in a round that is already closed",
+            ))));
+        }
+    }
+
+    // revert or reapply the deltas so changes made in between are kept
+    let team_ids: Vec<i32> = score_events.iter().map(|event| event.team_id).collect();
+    let deltas: Vec<i32> = score_events
+        .iter()
+        .map(|event| if undo { -event.delta } else { event.delta })
+        .collect();
+
+    let mut teams = Team::add_scores_tx(&mut tx, &scoreboard_id, &team_ids, &deltas).await?;
+
+    // dropping the transaction rolls the changes back when one breaks the scoring rules
+    for (team_id, delta) in team_ids.iter().zip(deltas.iter()) {
+        if let Some(team) = teams.iter().find(|team| team.team_id == *team_id) {
+            validate_score_change(&scoreboard, *delta, team.score)?;
+        }
+    }
+
+    // changes made in a round also change the score of the round
+    for (score_event, delta) in score_events.iter().zip(deltas.iter()) {
+        if let Some(round_id) = score_event.round_id {
+            Round::add_round_score_tx(&mut tx, &round_id, &score_event.team_id, delta).await?;
+        }
+    }
+
+    let mut score_events = ScoreEvent::set_undone_tx(&mut tx, &group_id, undo).await?;
+    score_events.sort_by_key(|event| event.score_event_id);
+
+    // the game ends once a team reaches the target score
+    let changed_teams: Vec<&Team> = teams.iter().collect();
+    if finish_if_target_reached_tx(&mut tx, &scoreboard, &changed_teams).await? {
+        let mut finished_teams = Vec::new();
+
+        for team_id in team_ids.iter() {
+            finished_teams.push(Team::get_team_tx(&mut tx, team_id).await?);
+        }
+
+        teams = finished_teams;
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::json(&ScoreEventResponse {
+        score_events,
+        teams,
+    }))
+}
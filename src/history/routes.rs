@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use super::handlers;
+use crate::wrappers::{with_auth, with_pool};
+
+pub struct HistoryRoutes;
+
+impl HistoryRoutes {
+    /// Init the history routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        get_history(pool.clone())
+            .or(undo(pool.clone()))
+            .or(redo(pool.clone()))
+    }
+}
+
+/// gets the score history of a scoreboard
+pub fn get_history(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "history")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_history)
+}
+
+/// undoes the latest score change of a scoreboard
+pub fn undo(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "undo")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::undo)
+}
+
+/// redoes the score change of a scoreboard that was undone last
+pub fn redo(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "redo")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::redo)
+}
@@ -0,0 +1,301 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+use super::handlers::CreateSeasonPayload;
+
+/// A group of users that plays seasons of games together
+#[derive(FromRow, Serialize, Debug)]
+pub struct League {
+    pub league_id: i32,
+    pub created_by: i32,
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// Representation of a member of a league along with their profile
+#[derive(FromRow, Serialize, Debug)]
+pub struct LeagueMember {
+    pub league_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub avatar_background: String,
+    pub avatar_emoji: String,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// A season of a league along with the points a win, a draw and a loss are worth
+#[derive(FromRow, Serialize, Debug)]
+pub struct Season {
+    pub season_id: i32,
+    pub league_id: i32,
+    pub name: String,
+    pub points_for_win: i32,
+    pub points_for_draw: i32,
+    pub points_for_loss: i32,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl League {
+    /// creates a league
+    pub async fn create_league_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        name: &str,
+        user_id: &i32,
+    ) -> Result<League> {
+        sqlx::query_as::<_, League>(
+            r#"
+                insert into
+                    leagues (name, created_by)
+                values
+                    ($1, $2)
+                returning
+                    *
+            "#,
+        )
+        .bind(name)
+        .bind(user_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a league
+    pub async fn get_league(conn: &PgPool, league_id: &i32) -> Result<Option<League>> {
+        sqlx::query_as::<_, League>(
+            r#"
+                select
+                    *
+                from
+                    leagues
+                where
+                    league_id = $1
+            "#,
+        )
+        .bind(league_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the leagues a user is a member of
+    pub async fn get_leagues_by_member(conn: &PgPool, user_id: &i32) -> Result<Vec<League>> {
+        sqlx::query_as::<_, League>(
+            r#"
+                select
+                    l.*
+                from
+                    leagues l
+                inner join
+                    league_members m
+                on
+                    l.league_id = m.league_id
+                where
+                    m.user_id = $1
+                order by
+                    l.name, l.league_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl LeagueMember {
+    /// fetches all members of a league
+    pub async fn get_members_by_league_id(
+        conn: &PgPool,
+        league_id: &i32,
+    ) -> Result<Vec<LeagueMember>> {
+        sqlx::query_as::<_, LeagueMember>(
+            r#"
+                select
+                    m.league_id, m.user_id, u.username, u.avatar_background, u.avatar_emoji, m.created_at
+                from
+                    league_members m
+                inner join
+                    users u
+                on
+                    m.user_id = u.user_id
+                where
+                    m.league_id = $1
+                order by
+                    m.created_at
+            "#,
+        )
+        .bind(league_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if a user is a member of a league
+    pub async fn is_member(conn: &PgPool, league_id: &i32, user_id: &i32) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select
+                        1
+                    from
+                        league_members
+                    where
+                        league_id = $1
+                    and
+                        user_id = $2
+                )
+            "#,
+        )
+        .bind(league_id)
+        .bind(user_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// adds a member to a league, returns false if the user is already a member
+    pub async fn add_member(conn: &PgPool, league_id: &i32, user_id: &i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                insert into
+                    league_members (league_id, user_id)
+                values
+                    ($1, $2)
+                on conflict do nothing
+            "#,
+        )
+        .bind(league_id)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// adds the creator of a newly created league
+    pub async fn add_creator_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        league_id: &i32,
+        user_id: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                insert into
+                    league_members (league_id, user_id)
+                values
+                    ($1, $2)
+            "#,
+        )
+        .bind(league_id)
+        .bind(user_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// removes a member that is not the creator, returns false if there is no such member
+    pub async fn remove_member(conn: &PgPool, league_id: &i32, user_id: &i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                delete from
+                    league_members m
+                using
+                    leagues l
+                where
+                    m.league_id = l.league_id
+                and
+                    m.league_id = $1
+                and
+                    m.user_id = $2
+                and
+                    m.user_id <> l.created_by
+            "#,
+        )
+        .bind(league_id)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+impl Season {
+    /// creates a season of a league
+    pub async fn create_season(
+        conn: &PgPool,
+        league_id: &i32,
+        payload: &CreateSeasonPayload,
+    ) -> Result<Season> {
+        sqlx::query_as::<_, Season>(
+            r#"
+                insert into
+                    seasons (league_id, name, points_for_win, points_for_draw, points_for_loss)
+                values
+                    ($1, $2, $3, $4, $5)
+                returning
+                    *
+            "#,
+        )
+        .bind(league_id)
+        .bind(&payload.name)
+        .bind(payload.points_for_win)
+        .bind(payload.points_for_draw)
+        .bind(payload.points_for_loss)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the seasons of a league
+    pub async fn get_seasons_by_league_id(conn: &PgPool, league_id: &i32) -> Result<Vec<Season>> {
+        sqlx::query_as::<_, Season>(
+            r#"
+                select
+                    *
+                from
+                    seasons
+                where
+                    league_id = $1
+                order by
+                    created_at, season_id
+            "#,
+        )
+        .bind(league_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a single season of a league
+    pub async fn get_season(
+        conn: &PgPool,
+        league_id: &i32,
+        season_id: &i32,
+    ) -> Result<Option<Season>> {
+        sqlx::query_as::<_, Season>(
+            r#"
+                select
+                    *
+                from
+                    seasons
+                where
+                    league_id = $1
+                and
+                    season_id = $2
+            "#,
+        )
+        .bind(league_id)
+        .bind(season_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
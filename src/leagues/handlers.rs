@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use futures::future;
+use jsonwebtoken::TokenData;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use validator::Validate;
+use warp::hyper::StatusCode;
+
+use crate::errors::TalliiError;
+use crate::members::db::Role;
+use crate::members::permissions::require_role;
+use crate::scoreboards::db::Scoreboard;
+use crate::scoreboards::handlers::get_scoreboard_response;
+use crate::teams::db::{Team, TeamMember};
+use crate::users::token::Claims;
+use crate::{ResponseResult, Result};
+
+use super::db::{League, LeagueMember, Season};
+use super::standings::{compute_standings, Standing};
+
+use crate::users;
+
+#[derive(Deserialize, Validate)]
+pub struct CreateLeaguePayload {
+    #[validate(length(min = 1))]
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct AddLeagueMemberPayload {
+    pub user_id: i32,
+}
+
+/// A new season, where a win is worth 3 points, a draw 1 and a loss nothing unless set otherwise
+#[derive(Deserialize, Validate)]
+pub struct CreateSeasonPayload {
+    #[validate(length(min = 1))]
+    pub name: String,
+    #[serde(default = "default_points_for_win")]
+    pub points_for_win: i32,
+    #[serde(default = "default_points_for_draw")]
+    pub points_for_draw: i32,
+    #[serde(default)]
+    pub points_for_loss: i32,
+}
+
+fn default_points_for_win() -> i32 {
+    3
+}
+
+fn default_points_for_draw() -> i32 {
+    1
+}
+
+#[derive(Deserialize)]
+pub struct AddSeasonScoreboardPayload {
+    pub scoreboard_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct LeagueResponse {
+    #[serde(flatten)]
+    pub league: League,
+    pub members: Vec<LeagueMember>,
+    pub seasons: Vec<Season>,
+}
+
+#[derive(Serialize)]
+pub struct StandingsResponse {
+    pub season: Season,
+    pub scoreboards_played: usize,
+    pub standings: Vec<Standing>,
+}
+
+/// makes sure the user is a member of the league
+async fn require_league_member(pool: &PgPool, league_id: &i32, user_id: &i32) -> Result<()> {
+    if !LeagueMember::is_member(pool, league_id, user_id).await? {
+        return Err(TalliiError::Forbidden);
+    }
+
+    Ok(())
+}
+
+/// makes sure the user created the league, which is who manages its members and seasons
+async fn require_league_creator(pool: &PgPool, league_id: &i32, user_id: &i32) -> Result<League> {
+    match League::get_league(pool, league_id).await? {
+        Some(league) if league.created_by == *user_id => Ok(league),
+        _ => Err(TalliiError::Forbidden),
+    }
+}
+
+/// builds the response of a league with its members and seasons
+async fn get_league_response(pool: &PgPool, league: League) -> Result<LeagueResponse> {
+    let (members, seasons) = future::try_join(
+        LeagueMember::get_members_by_league_id(pool, &league.league_id),
+        Season::get_seasons_by_league_id(pool, &league.league_id),
+    )
+    .await?;
+
+    Ok(LeagueResponse {
+        league,
+        members,
+        seasons,
+    })
+}
+
+/// creates a league with the user as its first member
+pub async fn create_league(
+    payload: CreateLeaguePayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let league = League::create_league_tx(&mut tx, &payload.name, &token.claims.sub).await?;
+
+    LeagueMember::add_creator_tx(&mut tx, &league.league_id, &token.claims.sub).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let response = get_league_response(&pool, league).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&response),
+        StatusCode::CREATED,
+    ))
+}
+
+/// gets the leagues the current user is a member of
+pub async fn get_my_leagues(
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let leagues = League::get_leagues_by_member(&pool, &token.claims.sub).await?;
+
+    Ok(warp::reply::json(&leagues))
+}
+
+/// gets a league with its members and seasons
+pub async fn get_league(
+    league_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only members can see a league
+    require_league_member(&pool, &league_id, &token.claims.sub).await?;
+
+    let league = League::get_league(&pool, &league_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    let response = get_league_response(&pool, league).await?;
+
+    Ok(warp::reply::json(&response))
+}
+
+/// adds a member to a league
+pub async fn add_league_member(
+    league_id: i32,
+    payload: AddLeagueMemberPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only the creator can add members
+    require_league_creator(&pool, &league_id, &token.claims.sub).await?;
+
+    // make sure the user exists
+    if users::db::User::get_by_user_id_option(&pool, &payload.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the user does not exist",
+        ))));
+    }
+
+    if !LeagueMember::add_member(&pool, &league_id, &payload.user_id).await? {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the user is already a member",
+        ))));
+    }
+
+    let members = LeagueMember::get_members_by_league_id(&pool, &league_id).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&members),
+        StatusCode::CREATED,
+    ))
+}
+
+/// removes a member from a league
+pub async fn remove_league_member(
+    league_id: i32,
+    user_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // members can leave a league on their own, otherwise only the creator can remove them
+    if user_id != token.claims.sub {
+        require_league_creator(&pool, &league_id, &token.claims.sub).await?;
+    }
+
+    // the creator can't be removed
+    if !LeagueMember::remove_member(&pool, &league_id, &user_id).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    Ok(warp::reply::with_status("member removed", StatusCode::OK))
+}
+
+/// creates a season of a league
+pub async fn create_season(
+    league_id: i32,
+    payload: CreateSeasonPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // only the creator can start seasons
+    require_league_creator(&pool, &league_id, &token.claims.sub).await?;
+
+    let season = Season::create_season(&pool, &league_id, &payload).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&season),
+        StatusCode::CREATED,
+    ))
+}
+
+/// attaches a scoreboard to a season of a league. members attach the scoreboards they own.
+pub async fn add_season_scoreboard(
+    league_id: i32,
+    season_id: i32,
+    payload: AddSeasonScoreboardPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    require_league_member(&pool, &league_id, &token.claims.sub).await?;
+
+    // make sure the season is part of the league
+    Season::get_season(&pool, &league_id, &season_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    require_role(
+        &pool,
+        &payload.scoreboard_id,
+        &token.claims.sub,
+        Role::Owner,
+    )
+    .await?;
+
+    // a scoreboard only counts towards one season, it has to be detached from the other one first
+    if !Scoreboard::set_season(&pool, &payload.scoreboard_id, &season_id).await? {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the scoreboard is already part of another season",
+        ))));
+    }
+
+    let response = get_scoreboard_response(pool, &payload.scoreboard_id).await?;
+
+    Ok(warp::reply::json(&response))
+}
+
+/// detaches a scoreboard from a season of a league. the owner of the scoreboard and the creator
+/// of the league can both detach it.
+pub async fn remove_season_scoreboard(
+    league_id: i32,
+    season_id: i32,
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let league = League::get_league(&pool, &league_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    if league.created_by != token.claims.sub {
+        require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+    }
+
+    // make sure the season is part of the league
+    Season::get_season(&pool, &league_id, &season_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    if !Scoreboard::remove_season(&pool, &scoreboard_id, &season_id).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    Ok(warp::reply::with_status(
+        "scoreboard detached",
+        StatusCode::OK,
+    ))
+}
+
+/// gets the standings of the members of a league in a season
+pub async fn get_standings(
+    league_id: i32,
+    season_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only members can see the standings
+    require_league_member(&pool, &league_id, &token.claims.sub).await?;
+
+    let season = Season::get_season(&pool, &league_id, &season_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    let scoreboards = Scoreboard::get_finished_scoreboards_by_season_id(&pool, &season_id).await?;
+    let scoreboard_ids: Vec<i32> = scoreboards
+        .iter()
+        .map(|scoreboard| scoreboard.scoreboard_id)
+        .collect();
+
+    // run the queries in parallel
+    let (league_members, teams, team_members) = future::try_join3(
+        LeagueMember::get_members_by_league_id(&pool, &league_id),
+        Team::get_teams_by_scoreboard_ids(&pool, &scoreboard_ids),
+        TeamMember::get_team_members_by_scoreboard_ids(&pool, &scoreboard_ids),
+    )
+    .await?;
+
+    let standings = compute_standings(&season, &league_members, &scoreboards, teams, team_members);
+
+    Ok(warp::reply::json(&StandingsResponse {
+        season,
+        scoreboards_played: scoreboards.len(),
+        standings,
+    }))
+}
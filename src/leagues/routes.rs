@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::wrappers::{with_auth, with_pool};
+
+use super::handlers;
+
+pub struct LeagueRoutes;
+
+impl LeagueRoutes {
+    /// Init the league routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        create_league(pool.clone())
+            .or(get_my_leagues(pool.clone()))
+            .or(get_league(pool.clone()))
+            .or(add_league_member(pool.clone()))
+            .or(remove_league_member(pool.clone()))
+            .or(create_season(pool.clone()))
+            .or(add_season_scoreboard(pool.clone()))
+            .or(remove_season_scoreboard(pool.clone()))
+            .or(get_standings(pool.clone()))
+    }
+}
+
+/// POST /v1/leagues
+pub fn create_league(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_league)
+}
+
+/// GET /v1/me/leagues
+pub fn get_my_leagues(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "leagues")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_my_leagues)
+}
+
+/// GET /v1/leagues/{id}
+pub fn get_league(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32)
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_league)
+}
+
+/// POST /v1/leagues/{id}/members
+pub fn add_league_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "members")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::add_league_member)
+}
+
+/// DELETE /v1/leagues/{id}/members/{user_id}
+pub fn remove_league_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "members" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::remove_league_member)
+}
+
+/// POST /v1/leagues/{id}/seasons
+pub fn create_season(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "seasons")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_season)
+}
+
+/// POST /v1/leagues/{id}/seasons/{season_id}/scoreboards
+pub fn add_season_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "seasons" / i32 / "scoreboards")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::add_season_scoreboard)
+}
+
+/// DELETE /v1/leagues/{id}/seasons/{season_id}/scoreboards/{scoreboard_id}
+pub fn remove_season_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "seasons" / i32 / "scoreboards" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::remove_season_scoreboard)
+}
+
+/// GET /v1/leagues/{id}/seasons/{season_id}/standings
+pub fn get_standings(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "leagues" / i32 / "seasons" / i32 / "standings")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_standings)
+}
@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::scoreboards::db::{Scoreboard, WinCondition};
+use crate::teams::db::{Team, TeamMember};
+
+use super::db::{LeagueMember, Season};
+
+/// The place of a member in the standings of a season
+#[derive(Serialize, Debug)]
+pub struct Standing {
+    pub rank: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub avatar_background: String,
+    pub avatar_emoji: String,
+    pub played: i32,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+    pub points: i32,
+    pub point_differential: i32,
+}
+
+impl Standing {
+    fn new(member: &LeagueMember) -> Self {
+        Standing {
+            rank: 0,
+            user_id: member.user_id,
+            username: member.username.clone(),
+            avatar_background: member.avatar_background.clone(),
+            avatar_emoji: member.avatar_emoji.clone(),
+            played: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+            point_differential: 0,
+        }
+    }
+
+    /// the order of the standings, where ties on points are broken by the point differential and
+    /// then by the number of wins
+    fn key(&self) -> (i32, i32, i32) {
+        (self.points, self.point_differential, self.wins)
+    }
+}
+
+/// Computes the standings of the members of a league from the finished scoreboards of a season.
+/// the winners of a scoreboard win it, unless they share the win with another team, which is a
+/// draw. the point differential of a team is its score against the best score of the other teams,
+/// turned around when the lowest score wins. teams count for the members that played on them,
+/// once per scoreboard.
+pub fn compute_standings(
+    season: &Season,
+    league_members: &[LeagueMember],
+    scoreboards: &[Scoreboard],
+    teams: Vec<Team>,
+    team_members: Vec<TeamMember>,
+) -> Vec<Standing> {
+    let mut standings: HashMap<i32, Standing> = league_members
+        .iter()
+        .map(|member| (member.user_id, Standing::new(member)))
+        .collect();
+
+    // group the teams by their scoreboard and the players by their team
+    let mut grouped_teams: HashMap<i32, Vec<Team>> = HashMap::new();
+    for team in teams.into_iter() {
+        grouped_teams
+            .entry(team.scoreboard_id)
+            .or_default()
+            .push(team);
+    }
+
+    let mut players: HashMap<i32, Vec<i32>> = HashMap::new();
+    for member in team_members.into_iter() {
+        if let Some(user_id) = member.user_id {
+            players.entry(member.team_id).or_default().push(user_id);
+        }
+    }
+
+    for scoreboard in scoreboards.iter() {
+        let teams = match grouped_teams.get(&scoreboard.scoreboard_id) {
+            Some(teams) if teams.len() > 1 => teams,
+            _ => continue,
+        };
+
+        let winners = teams.iter().filter(|team| team.is_winner).count();
+
+        // a user that played on more than one team of the scoreboard counts for the first one
+        let mut counted: HashSet<i32> = HashSet::new();
+
+        for team in teams.iter() {
+            let other_scores = teams
+                .iter()
+                .filter(|other| other.team_id != team.team_id)
+                .map(|other| other.score);
+
+            let point_differential = match scoreboard.win_condition {
+                WinCondition::Highest => team.score - other_scores.max().unwrap_or(team.score),
+                WinCondition::Lowest => other_scores.min().unwrap_or(team.score) - team.score,
+            };
+
+            for user_id in players.get(&team.team_id).into_iter().flatten() {
+                if !counted.insert(*user_id) {
+                    continue;
+                }
+
+                let standing = match standings.get_mut(user_id) {
+                    Some(standing) => standing,
+                    None => continue,
+                };
+
+                standing.played += 1;
+                standing.point_differential += point_differential;
+
+                if team.is_winner && winners == 1 {
+                    standing.wins += 1;
+                    standing.points += season.points_for_win;
+                } else if team.is_winner {
+                    standing.draws += 1;
+                    standing.points += season.points_for_draw;
+                } else {
+                    standing.losses += 1;
+                    standing.points += season.points_for_loss;
+                }
+            }
+        }
+    }
+
+    let mut standings: Vec<Standing> = standings.into_values().collect();
+    standings.sort_by(|a, b| {
+        b.key()
+            .cmp(&a.key())
+            .then_with(|| a.username.cmp(&b.username))
+    });
+
+    // members that are tied on every tiebreaker share their rank
+    let keys: Vec<(i32, i32, i32)> = standings.iter().map(Standing::key).collect();
+    for (index, standing) in standings.iter_mut().enumerate() {
+        let better = keys
+            .iter()
+            .take(index)
+            .filter(|key| **key > standing.key())
+            .count();
+        standing.rank = better as i32 + 1;
+    }
+
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::scoreboards::db::{Status, Visibility};
+
+    use super::*;
+
+    fn season() -> Season {
+        Season {
+            season_id: 1,
+            league_id: 1,
+            name: String::from("season"),
+            points_for_win: 3,
+            points_for_draw: 1,
+            points_for_loss: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn member(user_id: i32, username: &str) -> LeagueMember {
+        LeagueMember {
+            league_id: 1,
+            user_id,
+            username: String::from(username),
+            avatar_background: String::new(),
+            avatar_emoji: String::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn scoreboard(scoreboard_id: i32, win_condition: WinCondition) -> Scoreboard {
+        Scoreboard {
+            scoreboard_id,
+            name: String::from("scoreboard"),
+            game: String::from("game"),
+            game_id: None,
+            created_by: 1,
+            visibility: Visibility::Private,
+            status: Status::Finished,
+            win_condition,
+            finished_at: Some(Utc::now()),
+            rated_at: None,
+            season_id: Some(1),
+            target_score: None,
+            min_score: None,
+            max_score: None,
+            allow_negative: false,
+            score_step: 1,
+            version: 1,
+            updated_at: Utc::now(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn team(team_id: i32, scoreboard_id: i32, score: i32, is_winner: bool) -> Team {
+        Team {
+            team_id,
+            scoreboard_id,
+            name: format!("team {}", team_id),
+            score,
+            position: team_id,
+            is_winner,
+            version: 1,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn player(team_id: i32, user_id: i32) -> TeamMember {
+        TeamMember {
+            team_member_id: team_id * 100 + user_id,
+            team_id,
+            user_id: Some(user_id),
+            guest_name: None,
+            username: None,
+            avatar_background: None,
+            avatar_emoji: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn standing(standings: &[Standing], user_id: i32) -> &Standing {
+        standings
+            .iter()
+            .find(|standing| standing.user_id == user_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn wins_and_losses() {
+        let standings = compute_standings(
+            &season(),
+            &[member(1, "a"), member(2, "b")],
+            &[scoreboard(1, WinCondition::Highest)],
+            vec![team(1, 1, 10, true), team(2, 1, 4, false)],
+            vec![player(1, 1), player(2, 2)],
+        );
+
+        let winner = standing(&standings, 1);
+        assert_eq!((winner.rank, winner.played, winner.wins), (1, 1, 1));
+        assert_eq!((winner.points, winner.point_differential), (3, 6));
+
+        let loser = standing(&standings, 2);
+        assert_eq!((loser.rank, loser.played, loser.losses), (2, 1, 1));
+        assert_eq!((loser.points, loser.point_differential), (0, -6));
+    }
+
+    #[test]
+    fn shared_wins_are_draws() {
+        let standings = compute_standings(
+            &season(),
+            &[member(1, "a"), member(2, "b"), member(3, "c")],
+            &[scoreboard(1, WinCondition::Highest)],
+            vec![
+                team(1, 1, 7, true),
+                team(2, 1, 7, true),
+                team(3, 1, 2, false),
+            ],
+            vec![player(1, 1), player(2, 2), player(3, 3)],
+        );
+
+        for user_id in [1, 2] {
+            let drawn = standing(&standings, user_id);
+            assert_eq!((drawn.rank, drawn.draws, drawn.wins), (1, 1, 0));
+            assert_eq!((drawn.points, drawn.point_differential), (1, 0));
+        }
+
+        assert_eq!(standing(&standings, 3).rank, 3);
+    }
+
+    #[test]
+    fn lowest_score_wins() {
+        let standings = compute_standings(
+            &season(),
+            &[member(1, "a"), member(2, "b")],
+            &[scoreboard(1, WinCondition::Lowest)],
+            vec![team(1, 1, 72, true), team(2, 1, 80, false)],
+            vec![player(1, 1), player(2, 2)],
+        );
+
+        let winner = standing(&standings, 1);
+        assert_eq!(
+            (winner.rank, winner.wins, winner.point_differential),
+            (1, 1, 8)
+        );
+
+        let loser = standing(&standings, 2);
+        assert_eq!(
+            (loser.rank, loser.losses, loser.point_differential),
+            (2, 1, -8)
+        );
+    }
+
+    #[test]
+    fn tied_members_share_their_rank() {
+        let standings = compute_standings(
+            &season(),
+            &[
+                member(1, "d"),
+                member(2, "c"),
+                member(3, "b"),
+                member(4, "a"),
+            ],
+            &[
+                scoreboard(1, WinCondition::Highest),
+                scoreboard(2, WinCondition::Highest),
+            ],
+            vec![
+                team(1, 1, 5, true),
+                team(2, 1, 3, false),
+                team(3, 2, 5, true),
+                team(4, 2, 3, false),
+            ],
+            vec![player(1, 1), player(2, 3), player(3, 2), player(4, 4)],
+        );
+
+        // ties are listed by username
+        let ranks: Vec<(i32, i32)> = standings
+            .iter()
+            .map(|standing| (standing.user_id, standing.rank))
+            .collect();
+        assert_eq!(ranks, vec![(2, 1), (1, 1), (4, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn players_count_once_per_scoreboard() {
+        let standings = compute_standings(
+            &season(),
+            &[member(1, "a"), member(2, "b")],
+            &[scoreboard(1, WinCondition::Highest)],
+            vec![team(1, 1, 10, true), team(2, 1, 4, false)],
+            vec![player(1, 1), player(2, 1), player(2, 2)],
+        );
+
+        let twice = standing(&standings, 1);
+        assert_eq!((twice.played, twice.wins, twice.losses), (1, 1, 0));
+    }
+
+    #[test]
+    fn scoreboards_need_two_teams() {
+        let standings = compute_standings(
+            &season(),
+            &[member(1, "a")],
+            &[scoreboard(1, WinCondition::Highest)],
+            vec![team(1, 1, 10, true)],
+            vec![player(1, 1)],
+        );
+
+        assert_eq!(standing(&standings, 1).played, 0);
+    }
+}
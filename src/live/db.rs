@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// The most events that are loaded at once for a client that is catching up
+pub const REPLAY_PAGE_SIZE: i64 = 1000;
+
+/// Representation of a change to a scoreboard that is pushed to clients
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct ScoreboardEvent {
+    pub event_id: i64,
+    pub scoreboard_id: i32,
+    #[serde(rename = "type", alias = "event_type")]
+    pub event_type: String,
+    pub data: serde_json::Value,
+}
+
+impl ScoreboardEvent {
+    /// Gets a page of the events of a scoreboard that happened after the given event. a full
+    /// page means there may be more events after it.
+    pub async fn get_events_since(
+        pool: &PgPool,
+        scoreboard_id: &i32,
+        event_id: &i64,
+    ) -> Result<Vec<ScoreboardEvent>> {
+        sqlx::query_as::<_, ScoreboardEvent>(
+            r#"
+                select
+                    event_id, scoreboard_id, event_type, data
+                from
+                    scoreboard_events
+                where
+                    scoreboard_id = $1
+                and
+                    event_id > $2
+                order by
+                    event_id
+                limit
+                    $3
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(event_id)
+        .bind(REPLAY_PAGE_SIZE)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast;
+
+use super::db::ScoreboardEvent;
+
+/// The postgres channel that scoreboard events are published on
+pub const SCOREBOARD_EVENTS_CHANNEL: &str = "scoreboard_events";
+
+/// How many events can be buffered for a slow client before it starts missing events
+const EVENT_BUFFER_SIZE: usize = 1024;
+
+/// Sends scoreboard events to every connected client of this replica
+pub type EventSender = broadcast::Sender<ScoreboardEvent>;
+
+/// Listens for scoreboard events from postgres and broadcasts them to the clients connected to
+/// this replica. Every replica listens on the same channel so clients receive the events no
+/// matter which replica made the change. The listener uses its own connection so it doesn't
+/// hold on to one from the request pool.
+pub fn listen(database_url: String) -> EventSender {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+    let events = sender.clone();
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = forward_events(&database_url, &events).await {
+                log::error!("scoreboard event listener failed: {}", e);
+            }
+
+            // wait a bit before trying again so a database outage doesn't spin
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+
+    sender
+}
+
+/// Forwards the notifications on the scoreboard events channel to the event sender
+async fn forward_events(database_url: &str, events: &EventSender) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect(database_url).await?;
+    listener.listen(SCOREBOARD_EVENTS_CHANNEL).await?;
+
+    loop {
+        let notification = listener.recv().await?;
+
+        match serde_json::from_str::<ScoreboardEvent>(notification.payload()) {
+            // sending only fails when no clients are connected, which is fine
+            Ok(event) => {
+                let _ = events.send(event);
+            }
+            Err(e) => log::warn!("received an invalid scoreboard event: {}", e),
+        }
+    }
+}
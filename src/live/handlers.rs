@@ -0,0 +1,223 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{future, stream, SinkExt, StreamExt};
+use jsonwebtoken::TokenData;
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::time::{self, Instant};
+use warp::sse::Event;
+use warp::ws::{Message, WebSocket, Ws};
+
+use crate::members::permissions::require_view;
+use crate::users::session::Session;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::{ScoreboardEvent, REPLAY_PAGE_SIZE};
+use super::events::EventSender;
+
+/// How often an open websocket or event stream checks that its user can still follow the
+/// scoreboard
+const ACCESS_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The close code of a websocket whose user can no longer follow the scoreboard
+const POLICY_VIOLATION: u16 = 1008;
+
+/// upgrades the connection to a websocket that receives the changes of a scoreboard
+pub async fn live_scoreboard(
+    scoreboard_id: i32,
+    ws: Ws,
+    pool: Arc<PgPool>,
+    events: EventSender,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only users that can see the scoreboard can follow it
+    require_view(&pool, &scoreboard_id, &token.claims.sub).await?;
+
+    // subscribe before upgrading so no events are missed during the upgrade
+    let receiver = events.subscribe();
+
+    Ok(ws
+        .on_upgrade(move |socket| send_events(socket, scoreboard_id, receiver, pool, token.claims)))
+}
+
+/// checks that the token of an open websocket or event stream has not expired, its session has not been revoked
+/// and the user can still see the scoreboard. a failing check counts as no access, the client
+/// reconnects and is checked again.
+async fn has_access(pool: &PgPool, scoreboard_id: &i32, claims: &Claims) -> bool {
+    if claims.exp <= chrono::Utc::now().timestamp() {
+        return false;
+    }
+
+    if !matches!(Session::is_active(pool, &claims.sid).await, Ok(true)) {
+        return false;
+    }
+
+    require_view(pool, scoreboard_id, &claims.sub).await.is_ok()
+}
+
+/// sends the events of the scoreboard to the socket until either side closes or the user can no
+/// longer follow the scoreboard
+async fn send_events(
+    socket: WebSocket,
+    scoreboard_id: i32,
+    mut receiver: Receiver<ScoreboardEvent>,
+    pool: Arc<PgPool>,
+    claims: Claims,
+) {
+    let (mut sender, mut messages) = socket.split();
+
+    // access was just checked during the upgrade, so the first check is one interval away
+    let mut access_checks = time::interval_at(
+        Instant::now() + ACCESS_CHECK_INTERVAL,
+        ACCESS_CHECK_INTERVAL,
+    );
+
+    loop {
+        tokio::select! {
+            _ = access_checks.tick() => {
+                if !has_access(&pool, &scoreboard_id, &claims).await {
+                    let _ = sender
+                        .send(Message::close_with(POLICY_VIOLATION, "access revoked"))
+                        .await;
+                    break;
+                }
+            },
+            message = messages.next() => match message {
+                // the client doesn't send anything meaningful, only watch for it leaving
+                Some(Ok(message)) if !message.is_close() => continue,
+                _ => break,
+            },
+            event = receiver.recv() => match event {
+                Ok(event) if event.scoreboard_id == scoreboard_id => {
+                    let json = match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(_) => continue,
+                    };
+
+                    if sender.send(Message::text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                // skip events of other scoreboards and the ones that were dropped for being slow
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    let _ = sender.close().await;
+}
+
+/// streams the changes of a scoreboard as server sent events. clients that reconnect with the
+/// last-event-id header first receive the events they missed.
+pub async fn scoreboard_events(
+    scoreboard_id: i32,
+    last_event_id: Option<i64>,
+    pool: Arc<PgPool>,
+    events: EventSender,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only users that can see the scoreboard can follow it
+    require_view(&pool, &scoreboard_id, &token.claims.sub).await?;
+
+    // subscribe before loading the missed events so nothing falls in between
+    let receiver = events.subscribe();
+
+    // access was just checked, so the first check is one interval away. a failing check ends
+    // the stream.
+    let checks = time::interval_at(
+        Instant::now() + ACCESS_CHECK_INTERVAL,
+        ACCESS_CHECK_INTERVAL,
+    );
+    let access_checks = stream::unfold(
+        (checks, pool.clone(), token.claims),
+        move |(mut checks, pool, claims)| async move {
+            loop {
+                checks.tick().await;
+
+                if !has_access(&pool, &scoreboard_id, &claims).await {
+                    return Some((None, (checks, pool, claims)));
+                }
+            }
+        },
+    );
+
+    // replay the events the client missed while it was disconnected one page at a time until
+    // it has caught up. a failing page ends the stream, the client reconnects with the last
+    // event id it received and continues from there.
+    let replay = stream::unfold(last_event_id, move |after| {
+        let pool = pool.clone();
+
+        async move {
+            let after = after?;
+
+            match ScoreboardEvent::get_events_since(&pool, &scoreboard_id, &after).await {
+                Ok(page) => {
+                    let next = match page.last() {
+                        Some(event) if page.len() as i64 == REPLAY_PAGE_SIZE => {
+                            Some(event.event_id)
+                        }
+                        _ => None,
+                    };
+
+                    Some((
+                        stream::iter(page.into_iter().map(Some).collect::<Vec<_>>()),
+                        next,
+                    ))
+                }
+                Err(e) => {
+                    log::warn!(
+                        "failed to replay the events of scoreboard {}: {}",
+                        scoreboard_id,
+                        e
+                    );
+                    Some((stream::iter(vec![None]), None))
+                }
+            }
+        }
+    })
+    .flatten();
+
+    let live = stream::unfold(receiver, |mut receiver| async move {
+        // end the stream when events were dropped, the client reconnects with the last event
+        // id it received and catches up on the missed events
+        receiver
+            .recv()
+            .await
+            .ok()
+            .map(|event| (Some(event), receiver))
+    });
+
+    // live events that were already replayed are skipped. the stream ends once the live events
+    // end or the user loses access.
+    let sent_until = last_event_id.unwrap_or(0);
+    let events = replay.chain(live).chain(stream::once(future::ready(None)));
+    let events = stream::select(events, access_checks)
+        .take_while(|event| future::ready(event.is_some()))
+        .filter_map(future::ready)
+        .filter(move |event| future::ready(event.scoreboard_id == scoreboard_id))
+        .scan(sent_until, |sent_until, event| {
+            let fresh = event.event_id > *sent_until;
+            if fresh {
+                *sent_until = event.event_id;
+            }
+
+            future::ready(Some(if fresh { Some(event) } else { None }))
+        })
+        .filter_map(future::ready);
+
+    let stream = events.map(|event| {
+        Ok::<Event, Infallible>(
+            Event::default()
+                .id(event.event_id.to_string())
+                .event(event.event_type)
+                .data(event.data.to_string()),
+        )
+    });
+
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(stream)))
+}
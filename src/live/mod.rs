@@ -0,0 +1,4 @@
+pub mod db;
+pub mod events;
+pub mod handlers;
+pub mod routes;
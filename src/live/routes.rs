@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use super::events::EventSender;
+use super::handlers;
+use crate::wrappers::{with_auth, with_events, with_pool, with_query_auth};
+
+pub struct LiveRoutes;
+
+impl LiveRoutes {
+    /// Init the live routes
+    pub fn init(
+        pool: Arc<PgPool>,
+        events: EventSender,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        live_scoreboard(pool.clone(), events.clone())
+            .or(scoreboard_events(pool.clone(), events.clone()))
+    }
+}
+
+/// GET /v1/scoreboards/{id}/live - websocket that receives the changes of a scoreboard. since
+/// browsers can't set headers on websockets the token can also be passed as a query param.
+pub fn live_scoreboard(
+    pool: Arc<PgPool>,
+    events: EventSender,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "live")
+        .and(warp::get())
+        .and(warp::ws())
+        .and(with_pool(pool.clone()))
+        .and(with_events(events.clone()))
+        .and(
+            with_auth(pool.clone())
+                .or(with_query_auth(pool.clone()))
+                .unify(),
+        )
+        .and_then(handlers::live_scoreboard)
+}
+
+/// GET /v1/scoreboards/{id}/events - server sent events of the changes of a scoreboard. since
+/// EventSource can't set headers the token can also be passed as a query param.
+pub fn scoreboard_events(
+    pool: Arc<PgPool>,
+    events: EventSender,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "events")
+        .and(warp::get())
+        .and(warp::header::optional::<i64>("last-event-id"))
+        .and(with_pool(pool.clone()))
+        .and(with_events(events.clone()))
+        .and(
+            with_auth(pool.clone())
+                .or(with_query_auth(pool.clone()))
+                .unify(),
+        )
+        .and_then(handlers::scoreboard_events)
+}
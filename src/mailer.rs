@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::config::Config;
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// Representation of an email to send
+#[derive(Debug)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers emails to users
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+/// Creates the mailer from the config. Uses smtp when a host is configured and falls back
+/// to logging the emails otherwise.
+pub fn from_config(config: &Config) -> Arc<dyn Mailer> {
+    match &config.smtp_host {
+        Some(host) => {
+            Arc::new(SmtpMailer::new(config, host).expect("Failed to configure the smtp mailer"))
+        }
+        None => Arc::new(LogMailer::new(
+            config.mail_outbox_dir.as_ref().map(PathBuf::from),
+        )),
+    }
+}
+
+/// Sends emails through an smtp relay
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    /// Creates the smtp mailer
+    pub fn new(config: &Config, host: &str) -> Result<Self> {
+        let from = config
+            .mail_from
+            .parse::<Mailbox>()
+            .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+            .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(SmtpMailer {
+            from,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let to = email
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .body(email.body)
+            .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Logs emails instead of sending them and optionally writes them to an outbox directory.
+/// Meant for local development and tests.
+pub struct LogMailer {
+    outbox_dir: Option<PathBuf>,
+}
+
+impl LogMailer {
+    /// Creates the log mailer
+    pub fn new(outbox_dir: Option<PathBuf>) -> Self {
+        LogMailer { outbox_dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        log::info!(
+            "sending email to {} with subject \"{}\":\n{}",
+            email.to,
+            email.subject,
+            email.body
+        );
+
+        if let Some(outbox_dir) = &self.outbox_dir {
+            let contents = format!(
+                "To: {}\nSubject: {}\n\n{}\n",
+                email.to, email.subject, email.body
+            );
+
+            // the timestamp keeps the files in the order they were sent
+            let path = outbox_dir.join(format!(
+                "{}-{}.eml",
+                chrono::Utc::now().timestamp_millis(),
+                uuid::Uuid::new_v4()
+            ));
+
+            tokio::fs::create_dir_all(outbox_dir)
+                .await
+                .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+            tokio::fs::write(path, contents)
+                .await
+                .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
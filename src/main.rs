@@ -3,10 +3,22 @@ use std::sync::Arc;
 
 pub mod config;
 pub mod errors;
+pub mod etag;
+pub mod games;
+pub mod history;
+pub mod leagues;
+pub mod live;
+pub mod mailer;
+pub mod members;
+pub mod ratings;
+pub mod rounds;
 pub mod routes;
 pub mod scoreboards;
 pub mod search;
+pub mod stats;
 pub mod teams;
+pub mod templates;
+pub mod tournaments;
 pub mod users;
 pub mod wrappers;
 
@@ -24,14 +36,22 @@ async fn main() -> Result<()> {
     pretty_env_logger::init();
 
     // configure the databse pool
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&config.database_url)
-        .await
-        .expect("Failed to connect to database.");
+    let pool = Arc::new(
+        PgPoolOptions::new()
+            .max_connections(5)
+            .connect(&config.database_url)
+            .await
+            .expect("Failed to connect to database."),
+    );
+
+    // configure the mailer
+    let mailer = mailer::from_config(&config);
+
+    // start listening for scoreboard events
+    let events = live::events::listen(config.database_url.clone());
 
     // init the routes
-    let routes = routes::init(Arc::new(pool), config.clone());
+    let routes = routes::init(pool, config.clone(), mailer, events);
 
     // start the warp server
     warp::serve(routes).run(([0, 0, 0, 0], 6000)).await;
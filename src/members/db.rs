@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// The role a user has on a scoreboard
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "scoreboard_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl Role {
+    /// Checks if the role grants at least the permissions of the other role
+    pub fn includes(&self, other: Role) -> bool {
+        self.rank() >= other.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Role::Owner => 2,
+            Role::Editor => 1,
+            Role::Viewer => 0,
+        }
+    }
+}
+
+/// Representation of a member of a scoreboard along with their profile
+#[derive(FromRow, Serialize)]
+pub struct Member {
+    pub scoreboard_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub avatar_background: String,
+    pub avatar_emoji: String,
+    pub role: Role,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl Member {
+    /// fetches all members of a scoreboard
+    pub async fn get_members_by_scoreboard_id(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<Member>> {
+        sqlx::query_as::<_, Member>(
+            r#"
+                select
+                    m.scoreboard_id, m.user_id, u.username, u.avatar_background, u.avatar_emoji, m.role, m.created_at
+                from
+                    scoreboard_members m
+                inner join
+                    users u
+                on
+                    m.user_id = u.user_id
+                where
+                    m.scoreboard_id = $1
+                order by
+                    m.created_at
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the role of a user on a scoreboard
+    pub async fn get_role(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+        user_id: &i32,
+    ) -> Result<Option<Role>> {
+        sqlx::query_scalar::<_, Role>(
+            r#"
+                select
+                    role
+                from
+                    scoreboard_members
+                where
+                    scoreboard_id = $1
+                and
+                    user_id = $2
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// adds a member to a scoreboard, returns false if the user is already a member
+    pub async fn add_member(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+        user_id: &i32,
+        role: &Role,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                insert into
+                    scoreboard_members (scoreboard_id, user_id, role)
+                values
+                    ($1, $2, $3)
+                on conflict do nothing
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// adds the owner of a newly created scoreboard
+    pub async fn add_owner_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        user_id: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                insert into
+                    scoreboard_members (scoreboard_id, user_id, role)
+                values
+                    ($1, $2, 'owner')
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// changes the role of a member that is not the owner, returns false if there is no such member
+    pub async fn update_role(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+        user_id: &i32,
+        role: &Role,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                update
+                    scoreboard_members
+                set
+                    role = $1
+                where
+                    scoreboard_id = $2
+                and
+                    user_id = $3
+                and
+                    role <> 'owner'
+            "#,
+        )
+        .bind(role)
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// removes a member that is not the owner, returns false if there is no such member
+    pub async fn remove_member(conn: &PgPool, scoreboard_id: &i32, user_id: &i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                delete from
+                    scoreboard_members
+                where
+                    scoreboard_id = $1
+                and
+                    user_id = $2
+                and
+                    role <> 'owner'
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
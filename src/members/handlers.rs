@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use sqlx::PgPool;
+use warp::hyper::StatusCode;
+
+use crate::errors::TalliiError;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::{Member, Role};
+use super::permissions::require_role;
+
+use crate::users;
+
+#[derive(Deserialize)]
+pub struct AddMemberPayload {
+    pub user_id: i32,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberPayload {
+    pub role: Role,
+}
+
+/// gets all members of a scoreboard
+pub async fn get_members(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // any member can see who else is a member
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Viewer).await?;
+
+    let members = Member::get_members_by_scoreboard_id(&pool, &scoreboard_id).await?;
+
+    Ok(warp::reply::json(&members))
+}
+
+/// adds a member to a scoreboard
+pub async fn add_member(
+    scoreboard_id: i32,
+    payload: AddMemberPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only the owner can add members
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+
+    // a scoreboard only has a single owner
+    if payload.role == Role::Owner {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "a scoreboard can only have one owner",
+        ))));
+    }
+
+    // make sure the user exists
+    if users::db::User::get_by_user_id_option(&pool, &payload.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the user does not exist",
+        ))));
+    }
+
+    if !Member::add_member(&pool, &scoreboard_id, &payload.user_id, &payload.role).await? {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the user is already a member",
+        ))));
+    }
+
+    let members = Member::get_members_by_scoreboard_id(&pool, &scoreboard_id).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&members),
+        StatusCode::CREATED,
+    ))
+}
+
+/// changes the role of a member of a scoreboard
+pub async fn update_member(
+    scoreboard_id: i32,
+    user_id: i32,
+    payload: UpdateMemberPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only the owner can change roles
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+
+    // a scoreboard only has a single owner
+    if payload.role == Role::Owner {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "a scoreboard can only have one owner",
+        ))));
+    }
+
+    // the owner can't change their own role
+    if !Member::update_role(&pool, &scoreboard_id, &user_id, &payload.role).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    let members = Member::get_members_by_scoreboard_id(&pool, &scoreboard_id).await?;
+
+    Ok(warp::reply::json(&members))
+}
+
+/// removes a member from a scoreboard
+pub async fn remove_member(
+    scoreboard_id: i32,
+    user_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // members can leave a scoreboard on their own, otherwise only the owner can remove them
+    if user_id != token.claims.sub {
+        require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+    }
+
+    // the owner can't be removed
+    if !Member::remove_member(&pool, &scoreboard_id, &user_id).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    Ok(warp::reply::with_status("member removed", StatusCode::OK))
+}
@@ -0,0 +1,4 @@
+pub mod db;
+pub mod handlers;
+pub mod permissions;
+pub mod routes;
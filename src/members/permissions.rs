@@ -0,0 +1,35 @@
+use sqlx::PgPool;
+
+use super::db::{Member, Role};
+
+use crate::errors::TalliiError;
+use crate::scoreboards::db::Scoreboard;
+use crate::Result;
+
+/// Makes sure the user has at least the required role on the scoreboard and returns their
+/// actual role. Users that are not a member of the scoreboard are forbidden, unless they
+/// can't see it, so the ids of private scoreboards can't be guessed.
+pub async fn require_role(
+    conn: &PgPool,
+    scoreboard_id: &i32,
+    user_id: &i32,
+    required: Role,
+) -> Result<Role> {
+    match Member::get_role(conn, scoreboard_id, user_id).await? {
+        Some(role) if role.includes(required) => Ok(role),
+        Some(_) => Err(TalliiError::Forbidden),
+        None => match Scoreboard::is_visible_to(conn, scoreboard_id, user_id).await? {
+            Some(true) => Err(TalliiError::Forbidden),
+            _ => Err(TalliiError::NotFound),
+        },
+    }
+}
+
+/// Makes sure the user can see the scoreboard. Private scoreboards are only visible to
+/// their members, to everyone else they don't exist.
+pub async fn require_view(conn: &PgPool, scoreboard_id: &i32, user_id: &i32) -> Result<()> {
+    match Scoreboard::is_visible_to(conn, scoreboard_id, user_id).await? {
+        Some(true) => Ok(()),
+        Some(false) | None => Err(TalliiError::NotFound),
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use super::handlers;
+use crate::wrappers::{with_auth, with_pool};
+
+pub struct MemberRoutes;
+
+impl MemberRoutes {
+    /// Init the member routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        get_members(pool.clone())
+            .or(add_member(pool.clone()))
+            .or(update_member(pool.clone()))
+            .or(remove_member(pool.clone()))
+    }
+}
+
+/// gets all members of a scoreboard
+pub fn get_members(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "members")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_members)
+}
+
+/// adds a member to a scoreboard
+pub fn add_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "members")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::add_member)
+}
+
+/// changes the role of a member of a scoreboard
+pub fn update_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "members" / i32)
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::update_member)
+}
+
+/// removes a member from a scoreboard
+pub fn remove_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "members" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::remove_member)
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+use super::elo::INITIAL_RATING;
+
+/// The skill rating of a user in a game
+#[derive(FromRow, Serialize, Debug)]
+pub struct Rating {
+    pub user_id: i32,
+    pub game: String,
+    pub rating: f64,
+    pub games_rated: i32,
+    pub updated_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// A change to the rating of a user, along with the scoreboard that caused it
+#[derive(FromRow, Serialize, Debug)]
+pub struct RatingHistory {
+    pub rating_history_id: i32,
+    pub user_id: i32,
+    pub game: String,
+    pub scoreboard_id: Option<i32>,
+    pub rating_before: f64,
+    pub rating_after: f64,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// A place on the leaderboard of a game along with the profile of the user
+#[derive(FromRow, Serialize, Debug)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub user_id: i32,
+    pub username: String,
+    pub avatar_background: String,
+    pub avatar_emoji: String,
+    pub rating: f64,
+    pub games_rated: i32,
+}
+
+impl Rating {
+    /// fetches the ratings of the users in a game and locks them until the transaction ends, so
+    /// scoreboards of the same game are rated one after another. users that were never rated in
+    /// the game first get the initial rating, otherwise there would be no row to lock.
+    pub async fn get_ratings_for_update_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        game: &str,
+        user_ids: &[i32],
+    ) -> Result<Vec<Rating>> {
+        sqlx::query(
+            r#"
+                insert into
+                    ratings (user_id, game, rating)
+                select
+                    user_id, $1, $3
+                from
+                    unnest($2::integer[]) as r(user_id)
+                on conflict do nothing
+            "#,
+        )
+        .bind(game)
+        .bind(user_ids)
+        .bind(INITIAL_RATING)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        sqlx::query_as::<_, Rating>(
+            r#"
+                select
+                    *
+                from
+                    ratings
+                where
+                    game = $1
+                and
+                    user_id = any($2)
+                for update
+            "#,
+        )
+        .bind(game)
+        .bind(user_ids)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// stores the new ratings of the users in a game and records the changes in their history
+    pub async fn update_ratings_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        game: &str,
+        scoreboard_id: &i32,
+        user_ids: &[i32],
+        ratings_before: &[f64],
+        ratings_after: &[f64],
+    ) -> Result<Vec<RatingHistory>> {
+        sqlx::query(
+            r#"
+                insert into
+                    ratings (user_id, game, rating, games_rated)
+                select
+                    user_id, $1, rating, 1
+                from
+                    unnest($2::integer[], $3::double precision[]) as r(user_id, rating)
+                on conflict (user_id, game) do update set
+                    rating = excluded.rating,
+                    games_rated = ratings.games_rated + 1,
+                    updated_at = now()
+            "#,
+        )
+        .bind(game)
+        .bind(user_ids)
+        .bind(ratings_after)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        sqlx::query_as::<_, RatingHistory>(
+            r#"
+                insert into
+                    rating_history (user_id, game, scoreboard_id, rating_before, rating_after)
+                select
+                    user_id, $1, $2, rating_before, rating_after
+                from
+                    unnest($3::integer[], $4::double precision[], $5::double precision[]) as r(user_id, rating_before, rating_after)
+                returning
+                    *
+            "#,
+        )
+        .bind(game)
+        .bind(scoreboard_id)
+        .bind(user_ids)
+        .bind(ratings_before)
+        .bind(ratings_after)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the ratings of a user in every game they were rated in
+    pub async fn get_ratings_by_user_id(conn: &PgPool, user_id: &i32) -> Result<Vec<Rating>> {
+        sqlx::query_as::<_, Rating>(
+            r#"
+                select
+                    *
+                from
+                    ratings
+                where
+                    user_id = $1
+                order by
+                    rating desc, game
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the best rated users of a game
+    pub async fn get_leaderboard(
+        conn: &PgPool,
+        game: &str,
+        limit: &i64,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        sqlx::query_as::<_, LeaderboardEntry>(
+            r#"
+                select
+                    rank() over (order by r.rating desc) as rank,
+                    r.user_id, u.username, u.avatar_background, u.avatar_emoji, r.rating, r.games_rated
+                from
+                    ratings r
+                inner join
+                    users u
+                on
+                    r.user_id = u.user_id
+                where
+                    r.game = $1
+                order by
+                    r.rating desc, r.user_id
+                limit
+                    $2
+            "#,
+        )
+        .bind(game)
+        .bind(limit)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl RatingHistory {
+    /// fetches every change to the ratings of a user, oldest first
+    pub async fn get_rating_history_by_user_id(
+        conn: &PgPool,
+        user_id: &i32,
+    ) -> Result<Vec<RatingHistory>> {
+        sqlx::query_as::<_, RatingHistory>(
+            r#"
+                select
+                    *
+                from
+                    rating_history
+                where
+                    user_id = $1
+                order by
+                    created_at, rating_history_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
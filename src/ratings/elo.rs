@@ -0,0 +1,45 @@
+/// The rating of a player that has not been rated in a game yet
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// How much a single game can change a rating
+const K_FACTOR: f64 = 32.0;
+
+/// The chance of a player with the rating to win against a player with the other rating
+fn expected_score(rating: f64, other_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other_rating - rating) / 400.0))
+}
+
+/// Calculates how much the rating of every team changes in a free for all. the teams are passed
+/// as their rank along with the average rating of their players. every team plays against every
+/// other team, where a better rank is a win and a shared rank a draw. the changes are averaged
+/// over the opponents, so a game with many teams counts as much as one with two.
+pub fn rating_changes(teams: &[(i32, f64)]) -> Vec<f64> {
+    let opponents = teams.len().saturating_sub(1);
+
+    if opponents == 0 {
+        return vec![0.0; teams.len()];
+    }
+
+    teams
+        .iter()
+        .enumerate()
+        .map(|(index, (rank, rating))| {
+            let total: f64 = teams
+                .iter()
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != index)
+                .map(|(_, (other_rank, other_rating))| {
+                    let score = match rank.cmp(other_rank) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+
+                    score - expected_score(*rating, *other_rating)
+                })
+                .sum();
+
+            K_FACTOR * total / opponents as f64
+        })
+        .collect()
+}
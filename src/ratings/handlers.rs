@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use futures::future;
+use jsonwebtoken::TokenData;
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::TalliiError;
+use crate::games::db::Game;
+use crate::members::db::{Member, Role};
+use crate::members::permissions::require_role;
+use crate::scoreboards::db::{Scoreboard, Status};
+use crate::scoreboards::scoring::rank_teams;
+use crate::teams::db::{Team, TeamMember};
+use crate::users::db::User;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::{Rating, RatingHistory};
+use super::elo::{rating_changes, INITIAL_RATING};
+
+/// How many users are shown on the leaderboard of a game
+const LEADERBOARD_SIZE: i64 = 100;
+
+/// The rating of a user in a game along with how it changed over time
+#[derive(Serialize)]
+pub struct UserRatingResponse {
+    #[serde(flatten)]
+    pub rating: Rating,
+    pub history: Vec<RatingHistory>,
+}
+
+/// updates the ratings of the players of a finished scoreboard in its game
+pub async fn rate_scoreboard(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only the owner can change the ratings of the players
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // the scoreboard can only be rated once
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // only the final standings are rated
+    if scoreboard.status != Status::Finished {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "only finished scoreboards can be rated",
+        ))));
+    }
+
+    if scoreboard.rated_at.is_some() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the scoreboard is already rated",
+        ))));
+    }
+
+    let (teams, members, scoreboard_members) = future::try_join3(
+        Team::get_teams_by_scoreboard_id(&pool, &scoreboard_id),
+        TeamMember::get_team_members_by_scoreboard_ids(&pool, std::slice::from_ref(&scoreboard_id)),
+        Member::get_members_by_scoreboard_id(&pool, &scoreboard_id),
+    )
+    .await?;
+
+    // anyone can be put on a team, but only the members of the scoreboard agreed to take part
+    let member_ids: HashSet<i32> = scoreboard_members
+        .iter()
+        .map(|member| member.user_id)
+        .collect();
+
+    // guests have no ratings, so only the teams with users take part
+    let ranked_teams: Vec<(i32, Vec<i32>)> = rank_teams(teams, members, scoreboard.win_condition)
+        .into_iter()
+        .map(|team| {
+            let user_ids = team
+                .members
+                .iter()
+                .filter_map(|member| member.user_id)
+                .filter(|user_id| member_ids.contains(user_id))
+                .collect();
+
+            (team.rank, user_ids)
+        })
+        .filter(|(_, user_ids): &(i32, Vec<i32>)| !user_ids.is_empty())
+        .collect();
+
+    if ranked_teams.len() < 2 {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "at least two teams need members of the scoreboard as players to rate it",
+        ))));
+    }
+
+    let user_ids: Vec<i32> = ranked_teams
+        .iter()
+        .flat_map(|(_, user_ids)| user_ids.clone())
+        .collect();
+
+    let current_ratings: HashMap<i32, f64> =
+        Rating::get_ratings_for_update_tx(&mut tx, &scoreboard.game, &user_ids)
+            .await?
+            .into_iter()
+            .map(|rating| (rating.user_id, rating.rating))
+            .collect();
+
+    let rating_of = |user_id: &i32| {
+        current_ratings
+            .get(user_id)
+            .copied()
+            .unwrap_or(INITIAL_RATING)
+    };
+
+    // a team is as strong as its players are on average
+    let team_ratings: Vec<(i32, f64)> = ranked_teams
+        .iter()
+        .map(|(rank, user_ids)| {
+            let total: f64 = user_ids.iter().map(rating_of).sum();
+
+            (*rank, total / user_ids.len() as f64)
+        })
+        .collect();
+
+    // every player of a team gets the change of their team
+    let mut rated_user_ids: Vec<i32> = Vec::new();
+    let mut ratings_before: Vec<f64> = Vec::new();
+    let mut ratings_after: Vec<f64> = Vec::new();
+
+    for ((_, user_ids), change) in ranked_teams.iter().zip(rating_changes(&team_ratings)) {
+        for user_id in user_ids {
+            rated_user_ids.push(*user_id);
+            ratings_before.push(rating_of(user_id));
+            ratings_after.push(rating_of(user_id) + change);
+        }
+    }
+
+    let history = Rating::update_ratings_tx(
+        &mut tx,
+        &scoreboard.game,
+        &scoreboard_id,
+        &rated_user_ids,
+        &ratings_before,
+        &ratings_after,
+    )
+    .await?;
+
+    Scoreboard::set_rated_tx(&mut tx, &scoreboard_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::json(&history))
+}
+
+/// gets the best rated users of a game
+pub async fn get_leaderboard(
+    game: String,
+    pool: Arc<PgPool>,
+    _token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // games can have spaces and other characters that are encoded in the path
+    let game = percent_decode_str(&game).decode_utf8().map_err(|_err| {
+        warp::reject::custom(TalliiError::BadRequest(String::from("invalid game")))
+    })?;
+
+    // ratings are kept under the name of the catalog game, so aliases and other casings
+    // find the same leaderboard
+    let game = match Game::find_game_by_name(&pool, &game).await? {
+        Some(catalog_game) => catalog_game.name,
+        None => game.into_owned(),
+    };
+
+    let leaderboard = Rating::get_leaderboard(&pool, &game, &LEADERBOARD_SIZE).await?;
+
+    Ok(warp::reply::json(&leaderboard))
+}
+
+/// gets the ratings of a user in every game they were rated in
+pub async fn get_user_ratings(
+    user_id: i32,
+    pool: Arc<PgPool>,
+    _token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // if the user doesnt exist return with a 404
+    if User::get_by_user_id_option(&pool, &user_id)
+        .await?
+        .is_none()
+    {
+        return Err(warp::reject::not_found());
+    }
+
+    let (ratings, history) = future::try_join(
+        Rating::get_ratings_by_user_id(&pool, &user_id),
+        RatingHistory::get_rating_history_by_user_id(&pool, &user_id),
+    )
+    .await?;
+
+    // group the history by its game
+    let mut grouped_history: HashMap<String, Vec<RatingHistory>> = HashMap::new();
+    for change in history.into_iter() {
+        grouped_history
+            .entry(change.game.clone())
+            .or_default()
+            .push(change);
+    }
+
+    let response: Vec<UserRatingResponse> = ratings
+        .into_iter()
+        .map(|rating| UserRatingResponse {
+            history: grouped_history.remove(&rating.game).unwrap_or_default(),
+            rating,
+        })
+        .collect();
+
+    Ok(warp::reply::json(&response))
+}
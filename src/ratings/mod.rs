@@ -0,0 +1,4 @@
+pub mod db;
+pub mod elo;
+pub mod handlers;
+pub mod routes;
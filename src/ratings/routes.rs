@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::wrappers::{with_auth, with_pool};
+
+use super::handlers;
+
+pub struct RatingRoutes;
+
+impl RatingRoutes {
+    /// Init the rating routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        rate_scoreboard(pool.clone())
+            .or(get_leaderboard(pool.clone()))
+            .or(get_user_ratings(pool.clone()))
+    }
+}
+
+/// POST /v1/scoreboards/{id}/rate
+pub fn rate_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "rate")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::rate_scoreboard)
+}
+
+/// GET /v1/games/{game}/leaderboard
+pub fn get_leaderboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "games" / String / "leaderboard")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_leaderboard)
+}
+
+/// GET /v1/users/{id}/ratings
+pub fn get_user_ratings(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "users" / i32 / "ratings")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_user_ratings)
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// If scores can still be entered for a round
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "round_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RoundStatus {
+    Open,
+    Closed,
+}
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct Round {
+    pub round_id: i32,
+    pub scoreboard_id: i32,
+    pub number: i32,
+    pub status: RoundStatus,
+    pub opened_at: chrono::DateTime<chrono::offset::Utc>,
+    pub closed_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+}
+
+#[derive(FromRow, Serialize, Debug)]
+pub struct RoundScore {
+    pub round_id: i32,
+    pub team_id: i32,
+    pub score: i32,
+}
+
+impl Round {
+    /// fetches the rounds of a scoreboard in the order they were played
+    pub async fn get_rounds_by_scoreboard_id(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<Round>> {
+        sqlx::query_as::<_, Round>(
+            r#"
+                select
+                    *
+                from
+                    rounds
+                where
+                    scoreboard_id = $1
+                order by
+                    number
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the scores of every round of a scoreboard
+    pub async fn get_round_scores_by_scoreboard_id(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<RoundScore>> {
+        sqlx::query_as::<_, RoundScore>(
+            r#"
+                select
+                    rs.*
+                from
+                    round_scores rs
+                inner join
+                    rounds r
+                on
+                    rs.round_id = r.round_id
+                where
+                    r.scoreboard_id = $1
+                order by
+                    r.number, rs.team_id
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a round of a scoreboard by its number
+    pub async fn get_round_by_number_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        number: &i32,
+    ) -> Result<Option<Round>> {
+        sqlx::query_as::<_, Round>(
+            r#"
+                select
+                    *
+                from
+                    rounds
+                where
+                    scoreboard_id = $1 and number = $2
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(number)
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the round of a scoreboard that is currently open
+    pub async fn get_open_round_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Option<Round>> {
+        sqlx::query_as::<_, Round>(
+            r#"
+                select
+                    *
+                from
+                    rounds
+                where
+                    scoreboard_id = $1 and status = 'open'
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if a scoreboard is scored round by round
+    pub async fn has_rounds_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select 1 from rounds where scoreboard_id = $1
+                )
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// opens the next round of a scoreboard
+    pub async fn open_round_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Round> {
+        sqlx::query_as::<_, Round>(
+            r#"
+                insert into
+                    rounds (scoreboard_id, number)
+                values
+                    ($1, (select coalesce(max(number), 0) + 1 from rounds where scoreboard_id = $1))
+                returning
+                    *
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// closes a round so no more scores can be entered for it
+    pub async fn close_round_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        round_id: &i32,
+    ) -> Result<Round> {
+        sqlx::query_as::<_, Round>(
+            r#"
+                update
+                    rounds
+                set
+                    status = 'closed',
+                    closed_at = now()
+                where
+                    round_id = $1
+                returning
+                    *
+            "#,
+        )
+        .bind(round_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the scores of a single round
+    pub async fn get_round_scores_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        round_id: &i32,
+    ) -> Result<Vec<RoundScore>> {
+        sqlx::query_as::<_, RoundScore>(
+            r#"
+                select
+                    *
+                from
+                    round_scores
+                where
+                    round_id = $1
+            "#,
+        )
+        .bind(round_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// sets the scores of many teams for a round
+    pub async fn set_round_scores_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        round_id: &i32,
+        team_ids: &[i32],
+        scores: &[i32],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                insert into
+                    round_scores (round_id, team_id, score)
+                select
+                    $1, *
+                from
+                    unnest($2::integer[], $3::integer[])
+                on conflict (round_id, team_id) do update set
+                    score = excluded.score
+            "#,
+        )
+        .bind(round_id)
+        .bind(team_ids)
+        .bind(scores)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// adds the delta to the score of a team in a round
+    pub async fn add_round_score_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        round_id: &i32,
+        team_id: &i32,
+        delta: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                insert into
+                    round_scores (round_id, team_id, score)
+                values
+                    ($1, $2, $3)
+                on conflict (round_id, team_id) do update set
+                    score = round_scores.score + excluded.score
+            "#,
+        )
+        .bind(round_id)
+        .bind(team_id)
+        .bind(delta)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
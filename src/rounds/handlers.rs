@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use jsonwebtoken::TokenData;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use validator::Validate;
+use warp::hyper::StatusCode;
+
+use crate::errors::TalliiError;
+use crate::history::db::ScoreEvent;
+use crate::members::db::Role;
+use crate::members::permissions::require_role;
+use crate::scoreboards::db::Scoreboard;
+use crate::scoreboards::handlers::get_scoreboard_response;
+use crate::scoreboards::scoring::{finish_if_target_reached_tx, validate_score_change};
+use crate::teams::db::Team;
+use crate::users::token::Claims;
+use crate::{ResponseResult, Result};
+
+use super::db::{Round, RoundScore, RoundStatus};
+
+#[derive(Deserialize, Serialize)]
+pub struct TeamRoundScorePayload {
+    pub team_id: i32,
+    pub score: i32,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SetRoundScoresPayload {
+    #[validate(length(min = 1))]
+    pub scores: Vec<TeamRoundScorePayload>,
+}
+
+/// A round along with the scores the teams got in it
+#[derive(Serialize, Debug)]
+pub struct RoundResponse {
+    #[serde(flatten)]
+    pub round: Round,
+    pub scores: Vec<RoundScore>,
+}
+
+/// gets the rounds of a scoreboard along with their scores
+pub async fn get_round_responses(pool: &PgPool, scoreboard_id: &i32) -> Result<Vec<RoundResponse>> {
+    let (rounds, scores) = futures::future::try_join(
+        Round::get_rounds_by_scoreboard_id(pool, scoreboard_id),
+        Round::get_round_scores_by_scoreboard_id(pool, scoreboard_id),
+    )
+    .await?;
+
+    // group the scores by their round
+    let mut grouped_scores: HashMap<i32, Vec<RoundScore>> = HashMap::new();
+    for score in scores.into_iter() {
+        grouped_scores
+            .entry(score.round_id)
+            .or_default()
+            .push(score);
+    }
+
+    Ok(rounds
+        .into_iter()
+        .map(|round| RoundResponse {
+            scores: grouped_scores.remove(&round.round_id).unwrap_or_default(),
+            round,
+        })
+        .collect())
+}
+
+/// makes sure the scores of a scoreboard can be changed directly. once a scoreboard has rounds
+/// the totals are derived from the scores of the rounds.
+pub async fn require_no_rounds_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    scoreboard_id: &i32,
+) -> Result<()> {
+    if Round::has_rounds_tx(tx, scoreboard_id).await? {
+        return Err(TalliiError::BadRequest(String::from(
+            "the scores of this scoreboard are entered per round",
+        )));
+    }
+
+    Ok(())
+}
+
+/// opens the next round of a scoreboard
+pub async fn open_round(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // the people keeping score decide when a round starts
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // finished scoreboards don't get any more rounds
+    scoreboard.require_active()?;
+
+    // a round has to be closed before the next one starts
+    if Round::get_open_round_tx(&mut tx, &scoreboard_id)
+        .await?
+        .is_some()
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the current round has to be closed first",
+        ))));
+    }
+
+    // the totals of a scoreboard with rounds are the sums of its round scores, so the first
+    // round can only start before any points were scored
+    if !Round::has_rounds_tx(&mut tx, &scoreboard_id).await?
+        && Team::get_teams_by_scoreboard_id_tx(&mut tx, &scoreboard_id)
+            .await?
+            .iter()
+            .any(|team| team.score != 0)
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "rounds can only be started before any points are scored",
+        ))));
+    }
+
+    let round = Round::open_round_tx(&mut tx, &scoreboard_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&round),
+        StatusCode::CREATED,
+    ))
+}
+
+/// closes a round of a scoreboard
+pub async fn close_round(
+    scoreboard_id: i32,
+    number: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // the people keeping score decide when a round ends
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    let round = Round::get_round_by_number_tx(&mut tx, &scoreboard_id, &number)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    if round.status == RoundStatus::Closed {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the round is already closed",
+        ))));
+    }
+
+    let round = Round::close_round_tx(&mut tx, &round.round_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::json(&round))
+}
+
+/// enters the scores of the teams for a round. the totals of the teams change by the difference
+/// to the scores they had in the round before.
+pub async fn set_round_scores(
+    scoreboard_id: i32,
+    number: i32,
+    payload: SetRoundScoresPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // a team can only have one score per round
+    if !payload
+        .scores
+        .iter()
+        .map(|score| score.team_id)
+        .all_unique()
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "a team can only be included once",
+        ))));
+    }
+
+    // check if the user can perform this action
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // the scores of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    let round = Round::get_round_by_number_tx(&mut tx, &scoreboard_id, &number)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    if round.status == RoundStatus::Closed {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the round is closed",
+        ))));
+    }
+
+    // the totals change by the difference to the previous scores of the round
+    let previous_scores: HashMap<i32, i32> = Round::get_round_scores_tx(&mut tx, &round.round_id)
+        .await?
+        .into_iter()
+        .map(|score| (score.team_id, score.score))
+        .collect();
+
+    let team_ids: Vec<i32> = payload.scores.iter().map(|score| score.team_id).collect();
+    let scores: Vec<i32> = payload.scores.iter().map(|score| score.score).collect();
+    let deltas: Vec<i32> = payload
+        .scores
+        .iter()
+        .map(|score| score.score - previous_scores.get(&score.team_id).unwrap_or(&0))
+        .collect();
+
+    let updated_teams = Team::add_scores_tx(&mut tx, &scoreboard_id, &team_ids, &deltas).await?;
+
+    // dropping the transaction rolls it back
+    if updated_teams.len() != team_ids.len() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "every team has to be part of the scoreboard",
+        ))));
+    }
+
+    Round::set_round_scores_tx(&mut tx, &round.round_id, &team_ids, &scores).await?;
+
+    // record the changes of the scores in the history, they are undone together
+    let group_id = Uuid::new_v4();
+    let mut changed_teams: Vec<&Team> = Vec::new();
+
+    for (team_id, delta) in team_ids.iter().zip(deltas.iter()) {
+        if let Some(team) = updated_teams.iter().find(|team| team.team_id == *team_id) {
+            // dropping the transaction rolls the changes back when one breaks the scoring rules
+            validate_score_change(&scoreboard, *delta, team.score)?;
+
+            if *delta != 0 {
+                ScoreEvent::record_score_event_tx(
+                    &mut tx,
+                    team,
+                    delta,
+                    Some(round.round_id),
+                    &group_id,
+                    &token.claims.sub,
+                )
+                .await?;
+                changed_teams.push(team);
+            }
+        }
+    }
+
+    // the game ends once a team reaches the target score
+    finish_if_target_reached_tx(&mut tx, &scoreboard, &changed_teams).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // respond with the whole scoreboard since the totals, ranks and rounds all changed
+    let response = get_scoreboard_response(pool, &scoreboard_id).await?;
+
+    Ok(warp::reply::json(&response))
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use super::handlers;
+use crate::wrappers::{with_auth, with_pool};
+
+pub struct RoundRoutes;
+
+impl RoundRoutes {
+    /// Init the round routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        open_round(pool.clone())
+            .or(close_round(pool.clone()))
+            .or(set_round_scores(pool.clone()))
+    }
+}
+
+/// opens the next round of a scoreboard
+pub fn open_round(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "rounds")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::open_round)
+}
+
+/// closes a round of a scoreboard
+pub fn close_round(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "rounds" / i32 / "close")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::close_round)
+}
+
+/// enters the scores of a round
+pub fn set_round_scores(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "rounds" / i32 / "scores")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::set_round_scores)
+}
@@ -6,21 +6,45 @@ use warp::Filter;
 
 use crate::config::Config;
 use crate::errors::handle_rejection;
+use crate::games::routes::GameRoutes;
+use crate::history::routes::HistoryRoutes;
+use crate::leagues::routes::LeagueRoutes;
+use crate::live::events::EventSender;
+use crate::live::routes::LiveRoutes;
+use crate::mailer::Mailer;
 
+use crate::members::routes::MemberRoutes;
+use crate::ratings::routes::RatingRoutes;
+use crate::rounds::routes::RoundRoutes;
 use crate::scoreboards::routes::ScoreboardRoutes;
 use crate::search::routes::SearchRoutes;
+use crate::stats::routes::StatsRoutes;
 use crate::teams::routes::TeamRoutes;
+use crate::templates::routes::TemplateRoutes;
+use crate::tournaments::routes::TournamentRoutes;
 use crate::users::routes::AuthRoutes;
 
 /// Combines all of the routes together
 pub fn init(
-    pool: Arc<PgPool>, // database pool
-    config: Config,    // config
+    pool: Arc<PgPool>,       // database pool
+    config: Config,          // config
+    mailer: Arc<dyn Mailer>, // mailer
+    events: EventSender,     // scoreboard events
 ) -> impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone {
-    AuthRoutes::init(pool.clone(), config.clone())
-        .or(ScoreboardRoutes::init(pool.clone()))
+    AuthRoutes::init(pool.clone(), config.clone(), mailer.clone())
+        .or(ScoreboardRoutes::init(pool.clone(), config.clone()))
         .or(TeamRoutes::init(pool.clone()))
+        .or(MemberRoutes::init(pool.clone()))
+        .or(HistoryRoutes::init(pool.clone()))
+        .or(RoundRoutes::init(pool.clone()))
+        .or(LiveRoutes::init(pool.clone(), events.clone()))
         .or(SearchRoutes::init(pool.clone()))
+        .or(StatsRoutes::init(pool.clone()))
+        .or(RatingRoutes::init(pool.clone()))
+        .or(GameRoutes::init(pool.clone()))
+        .or(TemplateRoutes::init(pool.clone(), config.clone()))
+        .or(TournamentRoutes::init(pool.clone(), config.clone()))
+        .or(LeagueRoutes::init(pool.clone()))
         .with(warp::log("tallii-platform"))
         .recover(handle_rejection)
 }
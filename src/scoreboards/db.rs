@@ -1,22 +1,84 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool, Postgres, Transaction};
 
 use crate::errors::TalliiError;
 use crate::Result;
 
-use super::handlers::CreateScoreboardPayload;
+use super::handlers::{CreateScoreboardPayload, UpdateScoreboardPayload};
+
+/// Who can see a scoreboard
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "scoreboard_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// only members can see the scoreboard
+    #[default]
+    Private,
+    /// anyone with the id can see the scoreboard
+    Unlisted,
+    /// anyone can see the scoreboard and it is listed on the profile of its creator
+    Public,
+}
+
+/// Where a scoreboard is in its lifecycle
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "scoreboard_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// the game is being played and the scores can change
+    #[default]
+    Active,
+    /// the game is over, the scores are frozen and the winners are recorded
+    Finished,
+    /// the scoreboard was put away
+    Archived,
+}
+
+/// Which teams win a scoreboard
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "scoreboard_win_condition", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WinCondition {
+    /// the teams with the highest score win
+    #[default]
+    Highest,
+    /// the teams with the lowest score win, like in golf
+    Lowest,
+}
 
 #[derive(FromRow, Serialize)]
 pub struct Scoreboard {
     pub scoreboard_id: i32,
     pub name: String,
     pub game: String,
+    pub game_id: Option<i32>,
     pub created_by: i32,
+    pub visibility: Visibility,
+    pub status: Status,
+    pub win_condition: WinCondition,
+    pub finished_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+    pub rated_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+    pub season_id: Option<i32>,
+    pub target_score: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub allow_negative: bool,
+    pub score_step: i32,
+    pub version: i32,
     pub updated_at: chrono::DateTime<chrono::offset::Utc>,
     pub created_at: chrono::DateTime<chrono::offset::Utc>,
 }
 
 impl Scoreboard {
+    /// makes sure the scores of the scoreboard can still change
+    pub fn require_active(&self) -> Result<()> {
+        if self.status != Status::Active {
+            return Err(TalliiError::ScoreboardNotActive);
+        }
+
+        Ok(())
+    }
+
     /// fetches all scoreboards
     pub async fn get_scoreboards_by_user_id(
         conn: &PgPool,
@@ -39,6 +101,81 @@ impl Scoreboard {
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
+    /// fetches the scoreboards of a user that the viewer is allowed to list. the scoreboards of a
+    /// user are the ones they created or played on a team of, and on their own profile also every
+    /// scoreboard they are a member of. everyone else only sees the public ones and the ones they
+    /// are a member of themselves.
+    pub async fn get_visible_scoreboards_by_user_id(
+        conn: &PgPool,
+        user_id: &i32,
+        viewer_id: &i32,
+        status: Option<Status>,
+    ) -> Result<Vec<Scoreboard>> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                select
+                    s.*
+                from
+                    scoreboards s
+                where
+                    (
+                        s.created_by = $1
+                    or
+                        exists (
+                            select 1 from team_members tm inner join teams t on tm.team_id = t.team_id where t.scoreboard_id = s.scoreboard_id and tm.user_id = $1
+                        )
+                    or
+                        ($1 = $2 and exists (
+                            select 1 from scoreboard_members m where m.scoreboard_id = s.scoreboard_id and m.user_id = $1
+                        ))
+                    )
+                and
+                    (
+                        s.visibility = 'public'
+                    or
+                        exists (
+                            select 1 from scoreboard_members m where m.scoreboard_id = s.scoreboard_id and m.user_id = $2
+                        )
+                    )
+                and
+                    ($3::scoreboard_status is null or s.status = $3)
+                order by
+                    s.scoreboard_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(viewer_id)
+        .bind(status)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if the user can see the scoreboard. returns none if the scoreboard does not exist.
+    pub async fn is_visible_to(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+        user_id: &i32,
+    ) -> Result<Option<bool>> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select
+                    s.visibility <> 'private' or exists (
+                        select 1 from scoreboard_members m where m.scoreboard_id = s.scoreboard_id and m.user_id = $2
+                    )
+                from
+                    scoreboards s
+                where
+                    s.scoreboard_id = $1
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
     /// fetches a single scoreboard
     pub async fn get_scoreboard(conn: &PgPool, scoreboard_id: &i32) -> Result<Scoreboard> {
         sqlx::query_as::<_, Scoreboard>(
@@ -66,23 +203,271 @@ impl Scoreboard {
         sqlx::query_as::<_, Scoreboard>(
             r#"
                 insert into
-                    scoreboards (name, game, created_by)
+                    scoreboards (name, game, game_id, created_by, visibility, win_condition, target_score, min_score, max_score, allow_negative, score_step)
                 values
-                    ($1, $2, $3)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
                 returning
                     *
             "#,
         )
         .bind(&payload.name)
         .bind(&payload.game)
+        .bind(payload.game_id)
         .bind(user_id)
+        .bind(payload.visibility)
+        .bind(payload.win_condition.unwrap_or_default())
+        .bind(payload.target_score)
+        .bind(payload.min_score)
+        .bind(payload.max_score)
+        .bind(payload.allow_negative())
+        .bind(payload.score_step())
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// updates the fields of a scoreboard that are set in the payload
+    pub async fn update_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        payload: &UpdateScoreboardPayload,
+    ) -> Result<Scoreboard> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                update
+                    scoreboards
+                set
+                    name = coalesce($1, name),
+                    game = coalesce($2, game),
+                    visibility = coalesce($3, visibility),
+                    win_condition = coalesce($4, win_condition),
+                    target_score = case when $5 then $6 else target_score end,
+                    min_score = case when $7 then $8 else min_score end,
+                    max_score = case when $9 then $10 else max_score end,
+                    allow_negative = coalesce($11, allow_negative),
+                    score_step = coalesce($12, score_step),
+                    game_id = case when $2::text is not null then $13 else game_id end
+                where
+                    scoreboard_id = $14
+                returning
+                    *
+            "#,
+        )
+        .bind(&payload.name)
+        .bind(&payload.game)
+        .bind(payload.visibility)
+        .bind(payload.win_condition)
+        .bind(payload.target_score.is_some())
+        .bind(payload.target_score.flatten())
+        .bind(payload.min_score.is_some())
+        .bind(payload.min_score.flatten())
+        .bind(payload.max_score.is_some())
+        .bind(payload.max_score.flatten())
+        .bind(payload.allow_negative)
+        .bind(payload.score_step)
+        .bind(payload.game_id)
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// finishes a scoreboard and marks the teams with the best score as its winners
+    pub async fn finish_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard: &Scoreboard,
+    ) -> Result<Scoreboard> {
+        sqlx::query(
+            r#"
+                update
+                    teams
+                set
+                    is_winner = score = (
+                        select
+                            case when $2 = 'lowest' then min(score) else max(score) end
+                        from
+                            teams
+                        where
+                            scoreboard_id = $1
+                    )
+                where
+                    scoreboard_id = $1
+            "#,
+        )
+        .bind(scoreboard.scoreboard_id)
+        .bind(scoreboard.win_condition)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                update
+                    scoreboards
+                set
+                    status = 'finished',
+                    finished_at = now()
+                where
+                    scoreboard_id = $1
+                returning
+                    *
+            "#,
+        )
+        .bind(scoreboard.scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// archives a scoreboard
+    pub async fn archive_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Scoreboard> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                update
+                    scoreboards
+                set
+                    status = 'archived'
+                where
+                    scoreboard_id = $1
+                returning
+                    *
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// marks a scoreboard as rated, so the ratings of its players only change once
+    pub async fn set_rated_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Scoreboard> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                update
+                    scoreboards
+                set
+                    rated_at = now()
+                where
+                    scoreboard_id = $1
+                returning
+                    *
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the finished scoreboards of a season
+    pub async fn get_finished_scoreboards_by_season_id(
+        conn: &PgPool,
+        season_id: &i32,
+    ) -> Result<Vec<Scoreboard>> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                select
+                    *
+                from
+                    scoreboards
+                where
+                    season_id = $1
+                and
+                    finished_at is not null
+                order by
+                    finished_at
+            "#,
+        )
+        .bind(season_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// attaches a scoreboard to a season, returns false if it is already part of another season
+    pub async fn set_season(conn: &PgPool, scoreboard_id: &i32, season_id: &i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                update
+                    scoreboards
+                set
+                    season_id = $2
+                where
+                    scoreboard_id = $1
+                and
+                    (season_id is null or season_id = $2)
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(season_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// detaches a scoreboard from a season, returns false if it isn't part of the season
+    pub async fn remove_season(
+        conn: &PgPool,
+        scoreboard_id: &i32,
+        season_id: &i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                update
+                    scoreboards
+                set
+                    season_id = null
+                where
+                    scoreboard_id = $1
+                and
+                    season_id = $2
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(season_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// locks a scoreboard until the transaction ends, so changes to its scores are applied one
+    /// after another. team updates lock the scoreboard through the updated_at trigger as well.
+    pub async fn lock_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Scoreboard> {
+        sqlx::query_as::<_, Scoreboard>(
+            r#"
+                select
+                    *
+                from
+                    scoreboards
+                where
+                    scoreboard_id = $1
+                for update
+            "#,
+        )
+        .bind(scoreboard_id)
         .fetch_one(tx)
         .await
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
     /// deletes a scoreboard
-    pub async fn delete_scoreboard(pool: &PgPool, scoreboard_id: &i32) -> Result<()> {
+    pub async fn delete_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<()> {
         sqlx::query(
             r#"
             delete from
@@ -92,7 +477,7 @@ impl Scoreboard {
             "#,
         )
         .bind(scoreboard_id)
-        .execute(pool)
+        .execute(tx)
         .await
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
 
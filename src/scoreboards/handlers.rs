@@ -2,53 +2,252 @@ use futures::future;
 use std::collections::HashMap;
 use std::sync::Arc;
 use warp::hyper::StatusCode;
+use warp::Reply;
 
 use jsonwebtoken::TokenData;
-use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use validator::Validate;
 
 use itertools::Itertools;
 
+use crate::config::Config;
+use crate::etag::{check_if_match, etag, is_not_modified};
+use crate::games::db::Game;
+use crate::members::db::{Member, Role};
+use crate::members::permissions::{require_role, require_view};
+use crate::rounds::handlers::{get_round_responses, RoundResponse};
 use crate::teams::db::CreateTeamPayload;
+use crate::tournaments::db::TournamentMatch;
 use crate::users::token::Claims;
 use crate::{ResponseResult, Result};
 
 use super::db;
+use super::scoring::{rank_teams, validate_rules, RankedTeam};
 
 use crate::errors::TalliiError;
 use crate::teams;
 use crate::users;
 
+/// A new scoreboard. the game is either picked from the catalog with its id or named freely, in
+/// which case it is still matched with the catalog. the scoring settings that are left out are
+/// taken from the game of the catalog.
 #[derive(Deserialize)]
 pub struct CreateScoreboardPayload {
     pub name: String,
-    pub game: String,
+    pub game: Option<String>,
+    pub game_id: Option<i32>,
+    #[serde(default)]
+    pub visibility: db::Visibility,
+    pub win_condition: Option<db::WinCondition>,
+    pub target_score: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub allow_negative: Option<bool>,
+    pub score_step: Option<i32>,
     pub teams: Vec<CreateTeamPayload>,
 }
 
+impl CreateScoreboardPayload {
+    /// uses the game of the catalog and its settings for the ones that were left out
+    fn apply_game(&mut self, game: &Game) {
+        self.game = Some(game.name.clone());
+        self.game_id = Some(game.game_id);
+        self.win_condition = self.win_condition.or(Some(game.win_condition));
+        self.target_score = self.target_score.or(game.target_score);
+        self.min_score = self.min_score.or(game.min_score);
+        self.max_score = self.max_score.or(game.max_score);
+        self.allow_negative = self.allow_negative.or(Some(game.allow_negative));
+        self.score_step = self.score_step.or(Some(game.score_step));
+    }
+
+    /// the name, game, teams and settings of an existing scoreboard
+    pub fn from_scoreboard(scoreboard: &db::Scoreboard, teams: &[teams::db::Team]) -> Self {
+        CreateScoreboardPayload {
+            name: scoreboard.name.clone(),
+            game: Some(scoreboard.game.clone()),
+            game_id: scoreboard.game_id,
+            visibility: scoreboard.visibility,
+            win_condition: Some(scoreboard.win_condition),
+            target_score: scoreboard.target_score,
+            min_score: scoreboard.min_score,
+            max_score: scoreboard.max_score,
+            allow_negative: Some(scoreboard.allow_negative),
+            score_step: Some(scoreboard.score_step),
+            teams: teams
+                .iter()
+                .map(|team| CreateTeamPayload {
+                    name: team.name.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn allow_negative(&self) -> bool {
+        self.allow_negative.unwrap_or_else(default_allow_negative)
+    }
+
+    pub fn score_step(&self) -> i32 {
+        self.score_step.unwrap_or_else(default_score_step)
+    }
+}
+
+/// Scores can go below zero unless the scoreboard says otherwise
+fn default_allow_negative() -> bool {
+    true
+}
+
+/// Scores change one point at a time unless the scoreboard says otherwise
+fn default_score_step() -> i32 {
+    1
+}
+
+/// Changes to a scoreboard as a json merge patch. fields that are left out stay the same, only
+/// the optional scoring rules can be removed with null.
+#[derive(Deserialize, Validate)]
+pub struct UpdateScoreboardPayload {
+    #[serde(default, deserialize_with = "non_null")]
+    #[validate(length(min = 1))]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    #[validate(length(min = 1))]
+    pub game: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub game_id: Option<i32>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub visibility: Option<db::Visibility>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub win_condition: Option<db::WinCondition>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub target_score: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub min_score: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub max_score: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub allow_negative: Option<bool>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub score_step: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ScoreboardsQuery {
+    pub status: Option<db::Status>,
+}
+
+/// Deserializes a field that may be left out but can't be null
+fn non_null<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Deserializes a field that may be left out or removed with null
+fn nullable<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// looks up the game of a scoreboard in the catalog. the id has to be of a game of the catalog, a
+/// free text game is matched with the names and aliases and kept as it is without a match.
+async fn find_catalog_game(
+    pool: &PgPool,
+    game: &Option<String>,
+    game_id: &Option<i32>,
+) -> Result<Option<Game>> {
+    match (game_id, game) {
+        (Some(game_id), _) => match Game::get_game(pool, game_id).await? {
+            Some(game) => Ok(Some(game)),
+            None => Err(TalliiError::BadRequest(String::from(
+                "the game does not exist",
+            ))),
+        },
+        (None, Some(game)) => Game::find_game_by_name(pool, game).await,
+        (None, None) => Err(TalliiError::BadRequest(String::from(
+            "either a game or a game_id has to be set",
+        ))),
+    }
+}
+
+/// uses the game of the catalog for a new scoreboard, along with the settings of the game that
+/// were left out. the game has to be playable with the teams of the scoreboard.
+pub async fn apply_catalog_game(
+    pool: &PgPool,
+    payload: &mut CreateScoreboardPayload,
+) -> Result<()> {
+    if let Some(game) = find_catalog_game(pool, &payload.game, &payload.game_id).await? {
+        if !game.allows_teams(payload.teams.len()) {
+            return Err(TalliiError::BadRequest(format!(
+                "{} can't be played with {} teams",
+                game.name,
+                payload.teams.len()
+            )));
+        }
+
+        payload.apply_game(&game);
+    }
+
+    Ok(())
+}
+
 #[derive(Serialize)]
 pub struct ScoreboardResponse {
     pub scoreboard_id: i32,
     pub name: String,
     pub game: String,
+    pub game_id: Option<i32>,
+    pub visibility: db::Visibility,
+    pub status: db::Status,
+    pub win_condition: db::WinCondition,
+    pub finished_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+    pub rated_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+    pub season_id: Option<i32>,
+    pub target_score: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub allow_negative: bool,
+    pub score_step: i32,
+    pub version: i32,
     pub created_by: users::db::UserResponse,
     pub created_at: chrono::DateTime<chrono::offset::Utc>,
     pub updated_at: chrono::DateTime<chrono::offset::Utc>,
-    pub teams: Option<Vec<teams::db::Team>>,
+    pub teams: Option<Vec<RankedTeam>>,
+    pub rounds: Option<Vec<RoundResponse>>,
 }
 
-async fn get_scoreboard_response(
+/// builds the response of a single scoreboard with its teams and rounds
+pub async fn get_scoreboard_response(
     pool: Arc<PgPool>,
     scoreboard_id: &i32,
 ) -> Result<ScoreboardResponse> {
     // get the scoreboard future
-    let scoreboard_future = db::Scoreboard::get_scoreboard(&pool, &scoreboard_id);
+    let scoreboard_future = db::Scoreboard::get_scoreboard(&pool, scoreboard_id);
 
     // get the teams for the scoreboard future
-    let teams_future = teams::db::Team::get_teams_by_scoreboard_id(&pool, &scoreboard_id);
+    let teams_future = teams::db::Team::get_teams_by_scoreboard_id(&pool, scoreboard_id);
+
+    // get the players of the teams future
+    let members_future = teams::db::TeamMember::get_team_members_by_scoreboard_ids(
+        &pool,
+        std::slice::from_ref(scoreboard_id),
+    );
+
+    // get the rounds for the scoreboard future
+    let rounds_future = get_round_responses(&pool, scoreboard_id);
 
     // run the futures in parallel
-    let (scoreboard, teams) = future::try_join(scoreboard_future, teams_future).await?;
+    let (scoreboard, teams, members, rounds) = future::try_join4(
+        scoreboard_future,
+        teams_future,
+        members_future,
+        rounds_future,
+    )
+    .await?;
 
     // get user that created the scoreboard
     let user = users::db::User::get_by_user_id(&pool, &scoreboard.created_by).await?;
@@ -58,6 +257,19 @@ async fn get_scoreboard_response(
         scoreboard_id: scoreboard.scoreboard_id,
         name: scoreboard.name,
         game: scoreboard.game,
+        game_id: scoreboard.game_id,
+        visibility: scoreboard.visibility,
+        status: scoreboard.status,
+        win_condition: scoreboard.win_condition,
+        finished_at: scoreboard.finished_at,
+        rated_at: scoreboard.rated_at,
+        season_id: scoreboard.season_id,
+        target_score: scoreboard.target_score,
+        min_score: scoreboard.min_score,
+        max_score: scoreboard.max_score,
+        allow_negative: scoreboard.allow_negative,
+        score_step: scoreboard.score_step,
+        version: scoreboard.version,
         created_by: users::db::UserResponse {
             user_id: user.user_id,
             username: user.username,
@@ -68,28 +280,66 @@ async fn get_scoreboard_response(
         },
         created_at: scoreboard.created_at,
         updated_at: scoreboard.updated_at,
-        teams: Some(teams),
+        teams: Some(rank_teams(teams, members, scoreboard.win_condition)),
+        rounds: Some(rounds),
     })
 }
 
 /// creates a scoreboard
 pub async fn create_scoreboard(
-    payload: CreateScoreboardPayload,
+    mut payload: CreateScoreboardPayload,
     pool: Arc<PgPool>,
+    config: Config,
     token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
+    // games of the catalog bring their settings and how many teams can play them
+    apply_catalog_game(&pool, &mut payload).await?;
+
+    create_scoreboard_reply(&payload, pool, &config, &token.claims.sub).await
+}
+
+/// creates a copy of a scoreboard with the same teams and settings, with the scores reset
+pub async fn clone_scoreboard(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    config: Config,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // anyone that can see a scoreboard can start their own copy of it
+    require_view(&pool, &scoreboard_id, &token.claims.sub).await?;
+
+    let (scoreboard, teams) = future::try_join(
+        db::Scoreboard::get_scoreboard(&pool, &scoreboard_id),
+        teams::db::Team::get_teams_by_scoreboard_id(&pool, &scoreboard_id),
+    )
+    .await?;
+
+    let payload = CreateScoreboardPayload::from_scoreboard(&scoreboard, &teams);
+
+    create_scoreboard_reply(&payload, pool, &config, &token.claims.sub).await
+}
+
+/// creates a scoreboard for the user and responds with it like the get scoreboard response.
+/// creating a scoreboard, cloning one and creating one from a template all end up here.
+pub async fn create_scoreboard_reply(
+    payload: &CreateScoreboardPayload,
+    pool: Arc<PgPool>,
+    config: &Config,
+    user_id: &i32,
+) -> ResponseResult<impl warp::Reply> {
+    // make sure the scoring rules make sense
+    validate_rules(payload.min_score, payload.max_score, payload.score_step())?;
+
+    // users may have to verify their email before they can create scoreboards
+    require_verified_email(&pool, config, user_id).await?;
+
     // get the transaction
     let mut tx = pool
         .begin()
         .await
         .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
 
-    // create scoreboard
-    let scoreboard =
-        db::Scoreboard::create_scoreboard_tx(&mut tx, &payload, &token.claims.sub).await?;
-
-    // create teams
-    teams::db::Team::create_teams(&mut tx, &payload.teams, &scoreboard.scoreboard_id).await?;
+    let (scoreboard, _) = create_scoreboard_with_teams_tx(&mut tx, payload, user_id).await?;
 
     // commit the transaction
     tx.commit()
@@ -100,48 +350,209 @@ pub async fn create_scoreboard(
     let response = get_scoreboard_response(pool, &scoreboard.scoreboard_id).await?;
 
     // this response should be the same as the get scoreboard response
-    Ok(warp::reply::with_status(
-        warp::reply::json(&response),
-        StatusCode::CREATED,
+    let etag = etag(response.version);
+
+    Ok(warp::reply::with_header(
+        warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED),
+        "ETag",
+        etag,
     ))
 }
 
+/// checks that the user verified their email, when the config asks for it before scoreboards can
+/// be created
+pub async fn require_verified_email(pool: &PgPool, config: &Config, user_id: &i32) -> Result<()> {
+    if config.require_verified_email {
+        let user = users::db::User::get_by_user_id(pool, user_id).await?;
+
+        if user.email_verified_at.is_none() {
+            return Err(TalliiError::UserEmailNotVerified);
+        }
+    }
+
+    Ok(())
+}
+
+/// creates a scoreboard along with its teams, with the user as its owner
+pub async fn create_scoreboard_with_teams_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    payload: &CreateScoreboardPayload,
+    user_id: &i32,
+) -> Result<(db::Scoreboard, Vec<teams::db::Team>)> {
+    // create scoreboard
+    let scoreboard = db::Scoreboard::create_scoreboard_tx(tx, payload, user_id).await?;
+
+    // the creator owns the scoreboard
+    Member::add_owner_tx(tx, &scoreboard.scoreboard_id, user_id).await?;
+
+    // create teams
+    let teams =
+        teams::db::Team::create_teams(tx, &payload.teams, &scoreboard.scoreboard_id).await?;
+
+    Ok((scoreboard, teams))
+}
+
 /// gets a single scoreboard
 pub async fn get_scoreboard(
     scoreboard_id: i32,
+    if_none_match: Option<String>,
     pool: Arc<PgPool>,
-    _token: TokenData<Claims>,
+    token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
+    // private scoreboards can only be seen by their members
+    require_view(&pool, &scoreboard_id, &token.claims.sub).await?;
+
+    // the version also changes with the teams, so there is no need to fetch anything else
+    // when the client already has the latest version
+    let scoreboard = db::Scoreboard::get_scoreboard(&pool, &scoreboard_id).await?;
+
+    if is_not_modified(&if_none_match, scoreboard.version) {
+        return Ok(warp::reply::with_header(
+            StatusCode::NOT_MODIFIED,
+            "ETag",
+            etag(scoreboard.version),
+        )
+        .into_response());
+    }
+
     let scoreboard_response = get_scoreboard_response(pool.clone(), &scoreboard_id).await?;
+    let etag = etag(scoreboard_response.version);
 
-    Ok(warp::reply::json(&scoreboard_response))
+    Ok(
+        warp::reply::with_header(warp::reply::json(&scoreboard_response), "ETag", etag)
+            .into_response(),
+    )
 }
 
-/// gets all scoreboards where the created_by is the current user
-pub async fn get_user_scoreboards(
-    user_id: i32,
+/// updates the name, game and settings of a scoreboard
+pub async fn update_scoreboard(
+    scoreboard_id: i32,
+    if_match: Option<String>,
+    mut payload: UpdateScoreboardPayload,
     pool: Arc<PgPool>,
+    token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
-    // get all scoreboards for current user
-    let scoreboards_future = db::Scoreboard::get_scoreboards_by_user_id(&pool, &user_id);
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // editors can change the scoreboard but only the owner decides who can see it
+    let required_role = if payload.visibility.is_some() {
+        Role::Owner
+    } else {
+        Role::Editor
+    };
+
+    require_role(&pool, &scoreboard_id, &token.claims.sub, required_role).await?;
+
+    // a new game is matched with the catalog, which keeps the scoring settings as they are
+    let mut catalog_game = None;
+
+    if payload.game.is_some() || payload.game_id.is_some() {
+        match find_catalog_game(&pool, &payload.game, &payload.game_id).await? {
+            Some(game) => {
+                payload.game = Some(game.name.clone());
+                payload.game_id = Some(game.game_id);
+                catalog_game = Some(game);
+            }
+            None => payload.game_id = None,
+        }
+    }
 
-    // get all teams for the scoreboards of the current user
-    let teams_future = teams::db::Team::get_teams_by_scoreboard_created_by(&pool, &user_id);
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // lock the scoreboard so it can't change between checking its version and updating it
+    let scoreboard = db::Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // only update the version of the scoreboard the client knows about
+    check_if_match(&if_match, scoreboard.version)?;
+
+    // the new game has to be playable with the teams of the scoreboard
+    if let Some(game) = &catalog_game {
+        let teams = teams::db::Team::get_teams_by_scoreboard_id_tx(&mut tx, &scoreboard_id).await?;
+
+        if !game.allows_teams(teams.len()) {
+            return Err(warp::reject::custom(TalliiError::BadRequest(format!(
+                "{} can't be played with {} teams",
+                game.name,
+                teams.len()
+            ))));
+        }
+    }
 
-    // get the current user info
+    // the winners of a finished scoreboard are already decided
+    if payload.win_condition.is_some() || payload.target_score.is_some() {
+        scoreboard.require_active()?;
+    }
+
+    // make sure the scoring rules still make sense with the changes
+    validate_rules(
+        payload.min_score.unwrap_or(scoreboard.min_score),
+        payload.max_score.unwrap_or(scoreboard.max_score),
+        payload.score_step.unwrap_or(scoreboard.score_step),
+    )?;
+
+    // update the scoreboard
+    db::Scoreboard::update_scoreboard_tx(&mut tx, &scoreboard_id, &payload).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // respond with the whole scoreboard like the get scoreboard response
+    let response = get_scoreboard_response(pool, &scoreboard_id).await?;
+    let etag = etag(response.version);
+
+    Ok(warp::reply::with_header(
+        warp::reply::json(&response),
+        "ETag",
+        etag,
+    ))
+}
+
+/// gets all scoreboards of a user that the viewer is allowed to see
+pub async fn get_user_scoreboards(
+    user_id: i32,
+    viewer_id: i32,
+    query: ScoreboardsQuery,
+    pool: Arc<PgPool>,
+) -> ResponseResult<impl warp::Reply> {
+    // get all scoreboards of the user that the viewer can see
+    let scoreboards_future = db::Scoreboard::get_visible_scoreboards_by_user_id(
+        &pool,
+        &user_id,
+        &viewer_id,
+        query.status,
+    );
+
+    // get the user info
     let user_future = users::db::User::get_by_user_id_option(&pool, &user_id);
 
     // run the queries in parallel
-    let (scoreboards, teams, user_option) =
-        future::try_join3(scoreboards_future, teams_future, user_future).await?;
+    let (scoreboards, user_option) = future::try_join(scoreboards_future, user_future).await?;
 
     // if the user doesnt exist return with a 404
     if user_option.is_none() {
         return Err(warp::reject::not_found());
     }
 
-    // extract the user from the option
-    let user = user_option.unwrap();
+    // the scoreboards can include ones that were shared with the user, so get the teams
+    // and the creators of every scoreboard
+    let scoreboard_ids: Vec<i32> = scoreboards.iter().map(|s| s.scoreboard_id).collect();
+    let creator_ids: Vec<i32> = scoreboards.iter().map(|s| s.created_by).unique().collect();
+
+    let (teams, members, creators) = future::try_join3(
+        teams::db::Team::get_teams_by_scoreboard_ids(&pool, &scoreboard_ids),
+        teams::db::TeamMember::get_team_members_by_scoreboard_ids(&pool, &scoreboard_ids),
+        users::db::User::get_by_user_ids(&pool, &creator_ids),
+    )
+    .await?;
 
     // group the teams into a hashmap
     let mut grouped_teams: HashMap<i32, Vec<teams::db::Team>> = HashMap::new();
@@ -149,14 +560,59 @@ pub async fn get_user_scoreboards(
         grouped_teams.insert(scoreboard_id, teams.collect::<Vec<teams::db::Team>>());
     }
 
+    // group the players by the scoreboard of their team
+    let team_scoreboards: HashMap<i32, i32> = grouped_teams
+        .values()
+        .flatten()
+        .map(|team| (team.team_id, team.scoreboard_id))
+        .collect();
+    let mut grouped_members: HashMap<i32, Vec<teams::db::TeamMember>> = HashMap::new();
+    for member in members.into_iter() {
+        if let Some(scoreboard_id) = team_scoreboards.get(&member.team_id) {
+            grouped_members
+                .entry(*scoreboard_id)
+                .or_default()
+                .push(member);
+        }
+    }
+
+    // index the creators by their id
+    let creators: HashMap<i32, users::db::User> = creators
+        .into_iter()
+        .map(|user| (user.user_id, user))
+        .collect();
+
     // build the response
     let mut response: Vec<ScoreboardResponse> = Vec::new();
 
     for scoreboard in scoreboards.into_iter() {
+        let win_condition = scoreboard.win_condition;
+        let members = grouped_members
+            .remove(&scoreboard.scoreboard_id)
+            .unwrap_or_default();
+        let user = creators.get(&scoreboard.created_by).ok_or_else(|| {
+            warp::reject::custom(TalliiError::InternalServerError(String::from(
+                "scoreboard creator not found",
+            )))
+        })?;
+
         response.push(ScoreboardResponse {
             scoreboard_id: scoreboard.scoreboard_id,
             name: scoreboard.name,
             game: scoreboard.game,
+            game_id: scoreboard.game_id,
+            visibility: scoreboard.visibility,
+            status: scoreboard.status,
+            win_condition: scoreboard.win_condition,
+            finished_at: scoreboard.finished_at,
+            rated_at: scoreboard.rated_at,
+            season_id: scoreboard.season_id,
+            target_score: scoreboard.target_score,
+            min_score: scoreboard.min_score,
+            max_score: scoreboard.max_score,
+            allow_negative: scoreboard.allow_negative,
+            score_step: scoreboard.score_step,
+            version: scoreboard.version,
             created_at: scoreboard.created_at,
             updated_at: scoreboard.updated_at,
             created_by: users::db::UserResponse {
@@ -168,7 +624,11 @@ pub async fn get_user_scoreboards(
                 created_at: user.created_at,
             },
             // the remove is used to get the value itself instead of the borrowed reference
-            teams: grouped_teams.remove(&scoreboard.scoreboard_id),
+            teams: grouped_teams
+                .remove(&scoreboard.scoreboard_id)
+                .map(|teams| rank_teams(teams, members, win_condition)),
+            // the rounds are only included for single scoreboards
+            rounds: None,
         });
     }
 
@@ -176,22 +636,126 @@ pub async fn get_user_scoreboards(
     Ok(warp::reply::json(&response))
 }
 
+/// finishes a scoreboard, which freezes its scores and records its winners
+pub async fn finish_scoreboard(
+    scoreboard_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // the people keeping score decide when the game is over
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // no scores can change while the winners are determined
+    let scoreboard = db::Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // only active scoreboards can be finished
+    scoreboard.require_active()?;
+
+    db::Scoreboard::finish_scoreboard_tx(&mut tx, &scoreboard).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let response = get_scoreboard_response(pool, &scoreboard_id).await?;
+    let etag = etag(response.version);
+
+    Ok(warp::reply::with_header(
+        warp::reply::json(&response),
+        "ETag",
+        etag,
+    ))
+}
+
+/// archives a scoreboard
+pub async fn archive_scoreboard(
+    scoreboard_id: i32,
+    if_match: Option<String>,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // only the owner can put the scoreboard away
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // lock the scoreboard so it can't change between checking its version and archiving it
+    let scoreboard = db::Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // only archive the version of the scoreboard the client knows about
+    check_if_match(&if_match, scoreboard.version)?;
+
+    // a game is put away once it is over
+    if scoreboard.status != db::Status::Finished {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "only finished scoreboards can be archived",
+        ))));
+    }
+
+    db::Scoreboard::archive_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let response = get_scoreboard_response(pool, &scoreboard_id).await?;
+    let etag = etag(response.version);
+
+    Ok(warp::reply::with_header(
+        warp::reply::json(&response),
+        "ETag",
+        etag,
+    ))
+}
+
 /// deletes a specific scorebaord
 pub async fn delete_scoreboard(
     scoreboard_id: i32,
+    if_match: Option<String>,
     pool: Arc<PgPool>,
     token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
-    // get the scoreboard
-    let scoreboard = db::Scoreboard::get_scoreboard(&pool, &scoreboard_id).await?;
+    // only the owner can delete the scoreboard
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
 
-    // if the creator is not the same as the requester, forbid the action
-    if scoreboard.created_by != token.claims.sub {
-        return Err(warp::reject::custom(TalliiError::Forbidden));
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // lock the scoreboard so it can't change between checking its version and deleting it
+    let scoreboard = db::Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // only delete the version of the scoreboard the client knows about
+    check_if_match(&if_match, scoreboard.version)?;
+
+    // the match of a tournament is confirmed from its scoreboard
+    if TournamentMatch::is_played_on_scoreboard_tx(&mut tx, &scoreboard_id).await? {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the scoreboard is played for a tournament match that is not confirmed yet",
+        ))));
     }
 
     // delete the scoreboard
-    db::Scoreboard::delete_scoreboard(&pool, &scoreboard_id).await?;
+    db::Scoreboard::delete_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
 
     // response with the scoreboard deleted
     Ok(warp::reply::with_status(
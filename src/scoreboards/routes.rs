@@ -5,8 +5,10 @@ use sqlx::PgPool;
 use warp::Filter;
 
 use super::handlers;
+use super::handlers::ScoreboardsQuery;
+use crate::config::Config;
 use crate::users::token::Claims;
-use crate::wrappers::{with_auth, with_pool};
+use crate::wrappers::{with_auth, with_config, with_merge_patch, with_pool};
 
 pub struct ScoreboardRoutes;
 
@@ -14,24 +16,31 @@ impl ScoreboardRoutes {
     /// Init the scoreboard routes
     pub fn init(
         pool: Arc<PgPool>,
+        config: Config,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-        create_scoreboard(pool.clone())
+        create_scoreboard(pool.clone(), config.clone())
             .or(get_me_scoreboards(pool.clone()))
             .or(get_scoreboard(pool.clone()))
             .or(get_user_scoreboards(pool.clone()))
+            .or(update_scoreboard(pool.clone()))
+            .or(finish_scoreboard(pool.clone()))
+            .or(archive_scoreboard(pool.clone()))
             .or(delete_scoreboard(pool.clone()))
+            .or(clone_scoreboard(pool.clone(), config.clone()))
     }
 }
 
 /// creates a scoreboard
 pub fn create_scoreboard(
     pool: Arc<PgPool>,
+    config: Config,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "scoreboards")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_config(config.clone()))
+        .and(with_auth(pool.clone()))
         .and_then(handlers::create_scoreboard)
 }
 
@@ -41,8 +50,9 @@ pub fn get_scoreboard(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "scoreboards" / i32)
         .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::get_scoreboard)
 }
 
@@ -52,11 +62,14 @@ pub fn get_me_scoreboards(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "me" / "scoreboards")
         .and(warp::get())
+        .and(warp::query::<ScoreboardsQuery>())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
-        .and_then(move |pool: Arc<PgPool>, token: TokenData<Claims>| {
-            handlers::get_user_scoreboards(token.claims.sub, pool)
-        })
+        .and(with_auth(pool.clone()))
+        .and_then(
+            move |query: ScoreboardsQuery, pool: Arc<PgPool>, token: TokenData<Claims>| {
+                handlers::get_user_scoreboards(token.claims.sub, token.claims.sub, query, pool)
+            },
+        )
 }
 
 /// gets all scoreboards for the matching user
@@ -65,22 +78,76 @@ pub fn get_user_scoreboards(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "users" / i32 / "scoreboards")
         .and(warp::get())
+        .and(warp::query::<ScoreboardsQuery>())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(
-            move |user_id: i32, pool: Arc<PgPool>, _token: TokenData<Claims>| {
-                handlers::get_user_scoreboards(user_id, pool)
+            move |user_id: i32,
+                  query: ScoreboardsQuery,
+                  pool: Arc<PgPool>,
+                  token: TokenData<Claims>| {
+                handlers::get_user_scoreboards(user_id, token.claims.sub, query, pool)
             },
         )
 }
 
+/// updates a scoreboard with a json merge patch
+pub fn update_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32)
+        .and(warp::patch())
+        .and(warp::header::optional::<String>("if-match"))
+        .and(with_merge_patch())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::update_scoreboard)
+}
+
+/// finishes a scoreboard
+pub fn finish_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "finish")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::finish_scoreboard)
+}
+
+/// archives a scoreboard
+pub fn archive_scoreboard(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "archive")
+        .and(warp::post())
+        .and(warp::header::optional::<String>("if-match"))
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::archive_scoreboard)
+}
+
 /// deletes the provided user
 pub fn delete_scoreboard(
     pool: Arc<PgPool>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "scoreboards" / i32)
         .and(warp::delete())
+        .and(warp::header::optional::<String>("if-match"))
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::delete_scoreboard)
 }
+
+/// creates a copy of a scoreboard with its scores reset
+pub fn clone_scoreboard(
+    pool: Arc<PgPool>,
+    config: Config,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "clone")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::clone_scoreboard)
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::{Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::teams::db::{Team, TeamMember};
+use crate::Result;
+
+use super::db::{Scoreboard, WinCondition};
+
+/// A team along with its place on the scoreboard and its players
+#[derive(Serialize, Debug)]
+pub struct RankedTeam {
+    #[serde(flatten)]
+    pub team: Team,
+    pub rank: i32,
+    pub is_leader: bool,
+    pub members: Vec<TeamMember>,
+}
+
+/// Checks that the scoring rules of a scoreboard make sense
+pub fn validate_rules(
+    min_score: Option<i32>,
+    max_score: Option<i32>,
+    score_step: i32,
+) -> Result<()> {
+    if score_step < 1 {
+        return Err(TalliiError::ValidationError(String::from(
+            "the score step has to be at least 1",
+        )));
+    }
+
+    if let (Some(min), Some(max)) = (min_score, max_score) {
+        if min > max {
+            return Err(TalliiError::ValidationError(String::from(
+                "the min score can't be higher than the max score",
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a change to the score of a team follows the rules of the scoreboard
+pub fn validate_score_change(scoreboard: &Scoreboard, delta: i32, score: i32) -> Result<()> {
+    if delta % scoreboard.score_step != 0 {
+        return Err(TalliiError::ValidationError(format!(
+            "scores change in steps of {}",
+            scoreboard.score_step
+        )));
+    }
+
+    if !scoreboard.allow_negative && score < 0 {
+        return Err(TalliiError::ValidationError(String::from(
+            "scores can't be negative",
+        )));
+    }
+
+    if let Some(min_score) = scoreboard.min_score {
+        if score < min_score {
+            return Err(TalliiError::ValidationError(format!(
+                "scores can't be lower than {}",
+                min_score
+            )));
+        }
+    }
+
+    if let Some(max_score) = scoreboard.max_score {
+        if score > max_score {
+            return Err(TalliiError::ValidationError(format!(
+                "scores can't be higher than {}",
+                max_score
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if one of the teams reached the target score, which ends the game
+pub fn target_reached(scoreboard: &Scoreboard, teams: &[&Team]) -> bool {
+    match scoreboard.target_score {
+        Some(target_score) => teams.iter().any(|team| team.score >= target_score),
+        None => false,
+    }
+}
+
+/// Ranks the teams of a scoreboard. teams with the same score share a rank and the teams after
+/// them skip the shared places, so two leaders are followed by the third place. the players are
+/// added to the teams they play on.
+pub fn rank_teams(
+    teams: Vec<Team>,
+    members: Vec<TeamMember>,
+    win_condition: WinCondition,
+) -> Vec<RankedTeam> {
+    let scores: Vec<i32> = teams.iter().map(|team| team.score).collect();
+
+    // group the players by their team
+    let mut grouped_members: HashMap<i32, Vec<TeamMember>> = HashMap::new();
+    for member in members.into_iter() {
+        grouped_members
+            .entry(member.team_id)
+            .or_default()
+            .push(member);
+    }
+
+    teams
+        .into_iter()
+        .map(|team| {
+            let better_teams = scores
+                .iter()
+                .filter(|score| match win_condition {
+                    WinCondition::Highest => **score > team.score,
+                    WinCondition::Lowest => **score < team.score,
+                })
+                .count() as i32;
+
+            RankedTeam {
+                members: grouped_members.remove(&team.team_id).unwrap_or_default(),
+                team,
+                rank: better_teams + 1,
+                is_leader: better_teams == 0,
+            }
+        })
+        .collect()
+}
+
+/// finishes the scoreboard when one of the changed teams reached its target score. returns true
+/// when the scoreboard was finished, which also changes the teams that won.
+pub async fn finish_if_target_reached_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    scoreboard: &Scoreboard,
+    changed_teams: &[&Team],
+) -> Result<bool> {
+    if !target_reached(scoreboard, changed_teams) {
+        return Ok(false);
+    }
+
+    Scoreboard::finish_scoreboard_tx(tx, scoreboard).await?;
+
+    Ok(true)
+}
@@ -27,12 +27,10 @@ pub async fn search(
 
             let response = SearchResults { users };
 
-            return Ok(warp::reply::json(&response));
-        }
-        None => {
-            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
-                "Invalid query parameters",
-            ))));
+            Ok(warp::reply::json(&response))
         }
+        None => Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "Invalid query parameters",
+        )))),
     }
 }
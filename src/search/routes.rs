@@ -27,6 +27,6 @@ pub fn search(
         .and(warp::get())
         .and(warp::query::<HashMap<String, String>>())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::search)
 }
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// The record of a user in the games of a kind, or in all of their games when there is no game.
+/// a game counts as a win when the team of the user is in the lead, shared or not, against at
+/// least one other team.
+#[derive(FromRow, Serialize, Debug)]
+pub struct GameStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game: Option<String>,
+    pub games_played: i64,
+    pub wins: i64,
+    pub win_rate: f64,
+    pub total_points: i64,
+}
+
+/// The record of a user in the games they played against another user on a different team
+#[derive(FromRow, Serialize, Debug)]
+pub struct HeadToHead {
+    pub games_played: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+}
+
+impl GameStats {
+    /// counts the scoreboards a user created that the viewer can see
+    pub async fn count_created_scoreboards(
+        conn: &PgPool,
+        user_id: &i32,
+        viewer_id: &i32,
+    ) -> Result<i64> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+                select
+                    count(*)
+                from
+                    scoreboards s
+                where
+                    s.created_by = $1
+                and
+                    (
+                        $1 = $2
+                    or
+                        s.visibility = 'public'
+                    or
+                        exists (
+                            select 1 from scoreboard_members sm where sm.scoreboard_id = s.scoreboard_id and sm.user_id = $2
+                        )
+                    )
+            "#,
+        )
+        .bind(user_id)
+        .bind(viewer_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// sums up the games a user played that the viewer can see, per game and in total. the row of
+    /// the total has no game and comes last.
+    pub async fn get_game_stats_by_user_id(
+        conn: &PgPool,
+        user_id: &i32,
+        viewer_id: &i32,
+    ) -> Result<Vec<GameStats>> {
+        sqlx::query_as::<_, GameStats>(
+            r#"
+                with played as (
+                    select
+                        s.game,
+                        t.score,
+                        exists (
+                            select 1 from teams o where o.scoreboard_id = t.scoreboard_id and o.team_id <> t.team_id
+                        ) and not exists (
+                            select
+                                1
+                            from
+                                teams o
+                            where
+                                o.scoreboard_id = t.scoreboard_id
+                            and
+                                case when s.win_condition = 'lowest' then o.score < t.score else o.score > t.score end
+                        ) as is_win
+                    from
+                        team_members m
+                    inner join
+                        teams t
+                    on
+                        m.team_id = t.team_id
+                    inner join
+                        scoreboards s
+                    on
+                        t.scoreboard_id = s.scoreboard_id
+                    where
+                        m.user_id = $1
+                    and
+                        (
+                            $1 = $2
+                        or
+                            s.visibility = 'public'
+                        or
+                            exists (
+                                select 1 from scoreboard_members sm where sm.scoreboard_id = s.scoreboard_id and sm.user_id = $2
+                            )
+                        )
+                )
+                select
+                    game,
+                    count(*) as games_played,
+                    count(*) filter (where is_win) as wins,
+                    coalesce((count(*) filter (where is_win))::float8 / nullif(count(*), 0), 0) as win_rate,
+                    coalesce(sum(score), 0) as total_points
+                from
+                    played
+                group by
+                    grouping sets ((game), ())
+                order by
+                    grouping(game), games_played desc, game
+            "#,
+        )
+        .bind(user_id)
+        .bind(viewer_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl HeadToHead {
+    /// compares the scores of the teams of two users in the games they played against each other.
+    /// the players see all of those games, everyone else only the ones they can see.
+    pub async fn get_head_to_head(
+        conn: &PgPool,
+        user_id: &i32,
+        opponent_id: &i32,
+        viewer_id: &i32,
+    ) -> Result<HeadToHead> {
+        sqlx::query_as::<_, HeadToHead>(
+            r#"
+                select
+                    count(*) as games_played,
+                    count(*) filter (
+                        where case when s.win_condition = 'lowest' then t.score < o.score else t.score > o.score end
+                    ) as wins,
+                    count(*) filter (
+                        where case when s.win_condition = 'lowest' then t.score > o.score else t.score < o.score end
+                    ) as losses,
+                    count(*) filter (where t.score = o.score) as draws
+                from
+                    team_members m
+                inner join
+                    teams t
+                on
+                    m.team_id = t.team_id
+                inner join
+                    teams o
+                on
+                    o.scoreboard_id = t.scoreboard_id and o.team_id <> t.team_id
+                inner join
+                    team_members om
+                on
+                    om.team_id = o.team_id and om.user_id = $2
+                inner join
+                    scoreboards s
+                on
+                    t.scoreboard_id = s.scoreboard_id
+                where
+                    m.user_id = $1
+                and
+                    (
+                        $3 in ($1, $2)
+                    or
+                        s.visibility = 'public'
+                    or
+                        exists (
+                            select 1 from scoreboard_members sm where sm.scoreboard_id = s.scoreboard_id and sm.user_id = $3
+                        )
+                    )
+            "#,
+        )
+        .bind(user_id)
+        .bind(opponent_id)
+        .bind(viewer_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use futures::future;
+use jsonwebtoken::TokenData;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::TalliiError;
+use crate::users::db::User;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::{GameStats, HeadToHead};
+
+#[derive(Serialize)]
+pub struct UserStatsResponse {
+    pub user_id: i32,
+    pub scoreboards_created: i64,
+    #[serde(flatten)]
+    pub totals: GameStats,
+    pub games: Vec<GameStats>,
+}
+
+#[derive(Serialize)]
+pub struct HeadToHeadResponse {
+    pub user_id: i32,
+    pub opponent_id: i32,
+    #[serde(flatten)]
+    pub record: HeadToHead,
+}
+
+/// gets the statistics of a user over the scoreboards the caller can see
+pub async fn get_user_stats(
+    user_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // if the user doesnt exist return with a 404
+    if User::get_by_user_id_option(&pool, &user_id)
+        .await?
+        .is_none()
+    {
+        return Err(warp::reject::not_found());
+    }
+
+    // run the queries in parallel
+    let (scoreboards_created, mut games) = future::try_join(
+        GameStats::count_created_scoreboards(&pool, &user_id, &token.claims.sub),
+        GameStats::get_game_stats_by_user_id(&pool, &user_id, &token.claims.sub),
+    )
+    .await?;
+
+    // the totals over all games come last
+    let totals = games.pop().ok_or_else(|| {
+        warp::reject::custom(TalliiError::InternalServerError(String::from(
+            "total stats not found",
+        )))
+    })?;
+
+    Ok(warp::reply::json(&UserStatsResponse {
+        user_id,
+        scoreboards_created,
+        totals,
+        games,
+    }))
+}
+
+/// gets the record of a user in the games they played against another user
+pub async fn get_head_to_head(
+    user_id: i32,
+    opponent_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // users can't play against themselves
+    if user_id == opponent_id {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the opponent has to be another user",
+        ))));
+    }
+
+    // if one of the users doesnt exist return with a 404
+    let (user, opponent) = future::try_join(
+        User::get_by_user_id_option(&pool, &user_id),
+        User::get_by_user_id_option(&pool, &opponent_id),
+    )
+    .await?;
+
+    if user.is_none() || opponent.is_none() {
+        return Err(warp::reject::not_found());
+    }
+
+    let record =
+        HeadToHead::get_head_to_head(&pool, &user_id, &opponent_id, &token.claims.sub).await?;
+
+    Ok(warp::reply::json(&HeadToHeadResponse {
+        user_id,
+        opponent_id,
+        record,
+    }))
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::wrappers::{with_auth, with_pool};
+
+use super::handlers;
+
+pub struct StatsRoutes;
+
+impl StatsRoutes {
+    /// Init the stats routes
+    pub fn init(
+        pool: Arc<PgPool>,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        get_user_stats(pool.clone()).or(get_head_to_head(pool.clone()))
+    }
+}
+
+/// GET /v1/users/{id}/stats
+pub fn get_user_stats(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "users" / i32 / "stats")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_user_stats)
+}
+
+/// GET /v1/users/{id}/head-to-head/{opponent_id}
+pub fn get_head_to_head(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "users" / i32 / "head-to-head" / i32)
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_head_to_head)
+}
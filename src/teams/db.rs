@@ -12,6 +12,9 @@ pub struct Team {
     pub scoreboard_id: i32,
     pub name: String,
     pub score: i32,
+    pub position: i32,
+    pub is_winner: bool,
+    pub version: i32,
     pub created_at: chrono::DateTime<chrono::offset::Utc>,
 }
 
@@ -21,16 +24,49 @@ pub struct CreateTeamPayload {
 }
 
 impl Team {
-    /// fetches all teams
-    pub async fn get_teams(conn: &PgPool) -> Result<Vec<Team>> {
+    /// fetches all teams of the scoreboards the user is a member of
+    pub async fn get_teams_by_member(conn: &PgPool, user_id: &i32) -> Result<Vec<Team>> {
         sqlx::query_as::<_, Team>(
             r#"
                 select
-                    *
+                    t.*
                 from
-                    teams
+                    teams t
+                inner join
+                    scoreboard_members m
+                on
+                    t.scoreboard_id = m.scoreboard_id
+                where
+                    m.user_id = $1
+                order by
+                    t.scoreboard_id, t.position, t.team_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches all teams the user played on
+    pub async fn get_teams_by_player(conn: &PgPool, user_id: &i32) -> Result<Vec<Team>> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                select
+                    t.*
+                from
+                    teams t
+                inner join
+                    team_members m
+                on
+                    t.team_id = m.team_id
+                where
+                    m.user_id = $1
+                order by
+                    t.scoreboard_id, t.position, t.team_id
             "#,
         )
+        .bind(user_id)
         .fetch_all(conn)
         .await
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
@@ -49,6 +85,8 @@ impl Team {
                     teams
                 where
                     scoreboard_id = $1
+                order by
+                    position, team_id
                 "#,
         )
         .bind(scoreboard_id)
@@ -57,7 +95,53 @@ impl Team {
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
+    /// fetches all teams for a specific scoreboard in a transaction
+    pub async fn get_teams_by_scoreboard_id_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<Vec<Team>> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                select
+                    *
+                from
+                    teams
+                where
+                    scoreboard_id = $1
+                order by
+                    position, team_id
+                "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
     /// fetches all teams for many scoreboard ids
+    pub async fn get_teams_by_scoreboard_ids(
+        conn: &PgPool,
+        scoreboard_ids: &[i32],
+    ) -> Result<Vec<Team>> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                select
+                    *
+                from
+                    teams
+                where
+                    scoreboard_id = any($1)
+                order by
+                    scoreboard_id, position, team_id
+                "#,
+        )
+        .bind(scoreboard_ids)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches all teams for the scoreboards created by a user
     pub async fn get_teams_by_scoreboard_created_by(
         conn: &PgPool,
         user_id: &i32,
@@ -65,7 +149,7 @@ impl Team {
         sqlx::query_as::<_, Team>(
             r#"
               select
-                  team_id, t.scoreboard_id, t.name, t.score, t.created_at
+                  team_id, t.scoreboard_id, t.name, t.score, t.position, t.is_winner, t.version, t.created_at
               from
                   teams t
               inner join
@@ -75,7 +159,7 @@ impl Team {
               where
                   s.created_by = $1
               order by
-                s.scoreboard_id
+                s.scoreboard_id, t.position, t.team_id
               "#,
         )
         .bind(user_id)
@@ -102,44 +186,65 @@ impl Team {
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
-    /// creates many teams
+    /// fetches a single team inside of a transaction
+    pub async fn get_team_tx(tx: &mut Transaction<'_, Postgres>, team_id: &i32) -> Result<Team> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                select
+                    *
+                from
+                    teams
+                where
+                    team_id = $1
+                "#,
+        )
+        .bind(team_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// creates many teams, in the order they are passed
     pub async fn create_teams(
         tx: &mut Transaction<'_, Postgres>,
-        teams: &Vec<CreateTeamPayload>,
+        teams: &[CreateTeamPayload],
         scoreboard_id: &i32,
-    ) -> Result<Team> {
+    ) -> Result<Vec<Team>> {
         let mut names: Vec<&str> = Vec::new();
         let mut scoreboard_ids: Vec<i32> = Vec::new();
+        let mut positions: Vec<i32> = Vec::new();
         let owned_scoreboard_id = scoreboard_id.to_owned();
 
         // create the values
-        for team in teams.iter() {
+        for (position, team) in teams.iter().enumerate() {
             names.push(&team.name);
             scoreboard_ids.push(owned_scoreboard_id);
+            positions.push(position as i32);
         }
 
         sqlx::query_as::<_, Team>(
             r#"
                 insert into
-                    teams (name, scoreboard_id)
+                    teams (name, scoreboard_id, position)
                 select
                     *
                 from
-                    unnest($1, $2)
+                    unnest($1, $2, $3)
                 returning
                     *
             "#,
         )
         .bind(names)
         .bind(scoreboard_ids)
-        .fetch_one(tx)
+        .bind(positions)
+        .fetch_all(tx)
         .await
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
     /// updates a specific team
-    pub async fn update_team(
-        pool: &PgPool,
+    pub async fn update_team_tx(
+        tx: &mut Transaction<'_, Postgres>,
         team_id: &i32,
         payload: &UpdateTeamRequest,
     ) -> Result<Team> {
@@ -157,10 +262,312 @@ impl Team {
             "#,
         )
         .bind(&payload.name)
-        .bind(&payload.score)
+        .bind(payload.score)
+        .bind(team_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// adds the delta to the score of a team
+    pub async fn add_score_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        team_id: &i32,
+        delta: &i32,
+    ) -> Result<Team> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                update
+                    teams
+                set
+                    score = score + $1
+                where
+                    team_id = $2
+                returning
+                    *
+            "#,
+        )
+        .bind(delta)
+        .bind(team_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// adds the deltas to the scores of many teams of a scoreboard at once. teams that are not
+    /// part of the scoreboard are left untouched.
+    pub async fn add_scores_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        team_ids: &[i32],
+        deltas: &[i32],
+    ) -> Result<Vec<Team>> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                update
+                    teams t
+                set
+                    score = t.score + d.delta
+                from
+                    unnest($1::integer[], $2::integer[]) as d(team_id, delta)
+                where
+                    t.team_id = d.team_id and t.scoreboard_id = $3
+                returning
+                    t.*
+            "#,
+        )
+        .bind(team_ids)
+        .bind(deltas)
+        .bind(scoreboard_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// creates a team after the other teams of the scoreboard
+    pub async fn create_team_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        payload: &CreateTeamPayload,
+        scoreboard_id: &i32,
+    ) -> Result<Team> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                insert into
+                    teams (name, scoreboard_id, position)
+                values
+                    ($1, $2, (select coalesce(max(position) + 1, 0) from teams where scoreboard_id = $2))
+                returning
+                    *
+            "#,
+        )
+        .bind(&payload.name)
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// deletes a team
+    pub async fn delete_team_tx(tx: &mut Transaction<'_, Postgres>, team_id: &i32) -> Result<()> {
+        sqlx::query(
+            r#"
+                delete from
+                    teams
+                where
+                    team_id = $1
+            "#,
+        )
         .bind(team_id)
-        .fetch_one(pool)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// moves the teams of a scoreboard into the order of the passed ids
+    pub async fn update_positions_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        team_ids: &[i32],
+    ) -> Result<Vec<Team>> {
+        sqlx::query_as::<_, Team>(
+            r#"
+                update
+                    teams t
+                set
+                    position = o.position - 1
+                from
+                    unnest($1::integer[]) with ordinality as o(team_id, position)
+                where
+                    t.team_id = o.team_id and t.scoreboard_id = $2
+                returning
+                    t.*
+            "#,
+        )
+        .bind(team_ids)
+        .bind(scoreboard_id)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
+
+/// A player on a team, either a user along with their profile or a guest with just a name
+#[derive(FromRow, Serialize, Debug)]
+pub struct TeamMember {
+    pub team_member_id: i32,
+    pub team_id: i32,
+    pub user_id: Option<i32>,
+    pub guest_name: Option<String>,
+    pub username: Option<String>,
+    pub avatar_background: Option<String>,
+    pub avatar_emoji: Option<String>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl TeamMember {
+    /// fetches the players of all teams of the scoreboards
+    pub async fn get_team_members_by_scoreboard_ids(
+        conn: &PgPool,
+        scoreboard_ids: &[i32],
+    ) -> Result<Vec<TeamMember>> {
+        sqlx::query_as::<_, TeamMember>(
+            r#"
+                select
+                    m.team_member_id, m.team_id, m.user_id, m.guest_name, u.username, u.avatar_background, u.avatar_emoji, m.created_at
+                from
+                    team_members m
+                inner join
+                    teams t
+                on
+                    m.team_id = t.team_id
+                left join
+                    users u
+                on
+                    m.user_id = u.user_id
+                where
+                    t.scoreboard_id = any($1)
+                order by
+                    m.created_at, m.team_member_id
+            "#,
+        )
+        .bind(scoreboard_ids)
+        .fetch_all(conn)
         .await
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
+
+    /// fetches the players of a team
+    pub async fn get_team_members_by_team_id(
+        conn: &PgPool,
+        team_id: &i32,
+    ) -> Result<Vec<TeamMember>> {
+        sqlx::query_as::<_, TeamMember>(
+            r#"
+                select
+                    m.team_member_id, m.team_id, m.user_id, m.guest_name, u.username, u.avatar_background, u.avatar_emoji, m.created_at
+                from
+                    team_members m
+                left join
+                    users u
+                on
+                    m.user_id = u.user_id
+                where
+                    m.team_id = $1
+                order by
+                    m.created_at, m.team_member_id
+            "#,
+        )
+        .bind(team_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the user a player of a team is linked to, the inner option is none for guests
+    pub async fn get_team_member_user_id(
+        conn: &PgPool,
+        team_id: &i32,
+        team_member_id: &i32,
+    ) -> Result<Option<Option<i32>>> {
+        sqlx::query_scalar::<_, Option<i32>>(
+            r#"
+                select
+                    user_id
+                from
+                    team_members
+                where
+                    team_id = $1
+                and
+                    team_member_id = $2
+            "#,
+        )
+        .bind(team_id)
+        .bind(team_member_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if a user already plays on one of the teams of a scoreboard
+    pub async fn is_playing_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+        user_id: &i32,
+    ) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select
+                        1
+                    from
+                        team_members m
+                    inner join
+                        teams t
+                    on
+                        m.team_id = t.team_id
+                    where
+                        t.scoreboard_id = $1
+                    and
+                        m.user_id = $2
+                )
+            "#,
+        )
+        .bind(scoreboard_id)
+        .bind(user_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// adds a user or a guest to a team
+    pub async fn add_team_member_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        team_id: &i32,
+        user_id: &Option<i32>,
+        guest_name: &Option<String>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                insert into
+                    team_members (team_id, user_id, guest_name)
+                values
+                    ($1, $2, $3)
+            "#,
+        )
+        .bind(team_id)
+        .bind(user_id)
+        .bind(guest_name)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// removes a player from a team, returns false if there is no such player
+    pub async fn remove_team_member_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        team_id: &i32,
+        team_member_id: &i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                delete from
+                    team_members
+                where
+                    team_id = $1
+                and
+                    team_member_id = $2
+            "#,
+        )
+        .bind(team_id)
+        .bind(team_member_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
 }
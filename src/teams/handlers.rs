@@ -1,50 +1,104 @@
 use std::sync::Arc;
 
+use itertools::Itertools;
 use jsonwebtoken::TokenData;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+use warp::hyper::StatusCode;
+use warp::Reply;
 
 use crate::errors::TalliiError;
+use crate::etag::{check_if_match, etag, is_not_modified};
+use crate::games::db::Game;
+use crate::history::db::ScoreEvent;
+use crate::members::db::Role;
+use crate::members::permissions::{require_role, require_view};
+use crate::rounds::handlers::require_no_rounds_tx;
+use crate::scoreboards::db::Scoreboard;
+use crate::scoreboards::scoring::{finish_if_target_reached_tx, validate_score_change};
+use crate::tournaments::db::TournamentMatch;
+use crate::users;
 use crate::users::token::Claims;
 use crate::ResponseResult;
 
 use super::db;
 
-use crate::scoreboards;
-
 #[derive(Deserialize)]
 pub struct UpdateTeamRequest {
     pub name: String,
     pub score: i32,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateScorePayload {
+    pub delta: i32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TeamScorePayload {
+    pub team_id: i32,
+    pub delta: i32,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateScoresPayload {
+    #[validate(length(min = 1))]
+    pub scores: Vec<TeamScorePayload>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTeamOrderPayload {
+    pub team_ids: Vec<i32>,
+}
+
+/// A player to add to a team, either a user or a guest
+#[derive(Deserialize, Validate)]
+pub struct AddTeamMemberPayload {
+    pub user_id: Option<i32>,
+    #[validate(length(min = 1))]
+    pub guest_name: Option<String>,
+}
+
 /// gets a single team
 pub async fn get_team(
     team_id: i32,
+    if_none_match: Option<String>,
     pool: Arc<PgPool>,
-    _claims: TokenData<Claims>,
+    token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
     let team = db::Team::get_team(&pool, &team_id).await?;
 
-    // TODO check if the user has perms to get the team
+    // the team is only visible if its scoreboard is
+    require_view(&pool, &team.scoreboard_id, &token.claims.sub).await?;
+
+    let etag = etag(team.version);
+
+    // the client already has the latest version
+    if is_not_modified(&if_none_match, team.version) {
+        return Ok(
+            warp::reply::with_header(StatusCode::NOT_MODIFIED, "ETag", etag).into_response(),
+        );
+    }
 
-    Ok(warp::reply::json(&team))
+    Ok(warp::reply::with_header(warp::reply::json(&team), "ETag", etag).into_response())
 }
 
-/// gets all teams
+/// gets all teams of the scoreboards the user is a member of
 pub async fn get_teams(
     pool: Arc<PgPool>,
-    _claims: TokenData<Claims>,
+    token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
-    let teams = db::Team::get_teams(&pool).await?;
+    let teams = db::Team::get_teams_by_member(&pool, &token.claims.sub).await?;
 
-    // TODO: check if the user has perms to get the teams
     Ok(warp::reply::json(&teams))
 }
 
 /// updates a team
 pub async fn update_team(
     team_id: i32,
+    if_match: Option<String>,
     payload: UpdateTeamRequest,
     pool: Arc<PgPool>,
     token: TokenData<Claims>,
@@ -52,17 +106,507 @@ pub async fn update_team(
     // get the team
     let team = db::Team::get_team(&pool, &team_id).await?;
 
-    // get the scoreboard for the team
-    let scoreboard =
-        scoreboards::db::Scoreboard::get_scoreboard(&pool, &team.scoreboard_id).await?;
-
     // check if the user can perform this action
-    if scoreboard.created_by != token.claims.sub {
-        return Err(warp::reject::custom(TalliiError::Forbidden));
+    require_role(&pool, &team.scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &team.scoreboard_id).await?;
+
+    // the score could have changed since it was fetched
+    let previous_team = db::Team::get_team_tx(&mut tx, &team_id).await?;
+
+    // only update the version of the team the client knows about
+    check_if_match(&if_match, previous_team.version)?;
+
+    // the scores of finished scoreboards are frozen and have to follow the scoring rules
+    if payload.score != previous_team.score {
+        scoreboard.require_active()?;
+        require_no_rounds_tx(&mut tx, &scoreboard.scoreboard_id).await?;
+        validate_score_change(
+            &scoreboard,
+            payload.score - previous_team.score,
+            payload.score,
+        )?;
     }
 
     // update the team
-    let updated_team = db::Team::update_team(&pool, &team_id, &payload).await?;
+    let mut updated_team = db::Team::update_team_tx(&mut tx, &team_id, &payload).await?;
+
+    // record the change of the score in the history
+    if updated_team.score != previous_team.score {
+        ScoreEvent::record_score_event_tx(
+            &mut tx,
+            &updated_team,
+            &(updated_team.score - previous_team.score),
+            None,
+            &Uuid::new_v4(),
+            &token.claims.sub,
+        )
+        .await?;
+
+        // the game ends once a team reaches the target score
+        if finish_if_target_reached_tx(&mut tx, &scoreboard, &[&updated_team]).await? {
+            updated_team = db::Team::get_team_tx(&mut tx, &team_id).await?;
+        }
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let etag = etag(updated_team.version);
+
+    Ok(warp::reply::with_header(
+        warp::reply::json(&updated_team),
+        "ETag",
+        etag,
+    ))
+}
+
+/// adds to or subtracts from the score of a team
+pub async fn update_score(
+    team_id: i32,
+    payload: UpdateScorePayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the team
+    let team = db::Team::get_team(&pool, &team_id).await?;
+
+    // check if the user can perform this action
+    require_role(&pool, &team.scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &team.scoreboard_id).await?;
+
+    // the scores of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    // scoreboards with rounds get their scores through the rounds
+    require_no_rounds_tx(&mut tx, &scoreboard.scoreboard_id).await?;
+
+    // the delta is applied in the database so concurrent changes are not lost
+    let mut updated_team = db::Team::add_score_tx(&mut tx, &team_id, &payload.delta).await?;
+
+    // dropping the transaction rolls the change back when it breaks the scoring rules
+    validate_score_change(&scoreboard, payload.delta, updated_team.score)?;
+
+    // record the change of the score in the history
+    if payload.delta != 0 {
+        ScoreEvent::record_score_event_tx(
+            &mut tx,
+            &updated_team,
+            &payload.delta,
+            None,
+            &Uuid::new_v4(),
+            &token.claims.sub,
+        )
+        .await?;
+
+        // the game ends once a team reaches the target score
+        if finish_if_target_reached_tx(&mut tx, &scoreboard, &[&updated_team]).await? {
+            updated_team = db::Team::get_team_tx(&mut tx, &team_id).await?;
+        }
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
 
     Ok(warp::reply::json(&updated_team))
 }
+
+/// adds to or subtracts from the scores of many teams of a scoreboard at once
+pub async fn update_scores(
+    scoreboard_id: i32,
+    payload: UpdateScoresPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // a team can only be changed once per request
+    if !payload
+        .scores
+        .iter()
+        .map(|score| score.team_id)
+        .all_unique()
+    {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "a team can only be included once",
+        ))));
+    }
+
+    // check if the user can perform this action
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    let team_ids: Vec<i32> = payload.scores.iter().map(|score| score.team_id).collect();
+    let deltas: Vec<i32> = payload.scores.iter().map(|score| score.delta).collect();
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // changes to the scores of a scoreboard are applied one after another
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // the scores of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    // scoreboards with rounds get their scores through the rounds
+    require_no_rounds_tx(&mut tx, &scoreboard.scoreboard_id).await?;
+
+    // the deltas are applied in a single statement so either all or none of them are
+    let mut updated_teams =
+        db::Team::add_scores_tx(&mut tx, &scoreboard_id, &team_ids, &deltas).await?;
+
+    // dropping the transaction rolls it back
+    if updated_teams.len() != team_ids.len() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "every team has to be part of the scoreboard",
+        ))));
+    }
+
+    // record the changes of the scores in the history, they are undone together
+    let group_id = Uuid::new_v4();
+    let mut changed_teams: Vec<&db::Team> = Vec::new();
+
+    for score in payload.scores.iter() {
+        if let Some(team) = updated_teams
+            .iter()
+            .find(|team| team.team_id == score.team_id)
+        {
+            // dropping the transaction rolls the changes back when one breaks the scoring rules
+            validate_score_change(&scoreboard, score.delta, team.score)?;
+
+            if score.delta != 0 {
+                ScoreEvent::record_score_event_tx(
+                    &mut tx,
+                    team,
+                    &score.delta,
+                    None,
+                    &group_id,
+                    &token.claims.sub,
+                )
+                .await?;
+                changed_teams.push(team);
+            }
+        }
+    }
+
+    // the game ends once a team reaches the target score
+    if finish_if_target_reached_tx(&mut tx, &scoreboard, &changed_teams).await? {
+        let mut finished_teams = Vec::new();
+
+        for team_id in team_ids.iter() {
+            finished_teams.push(db::Team::get_team_tx(&mut tx, team_id).await?);
+        }
+
+        updated_teams = finished_teams;
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    updated_teams.sort_by_key(|team| team.team_id);
+
+    Ok(warp::reply::json(&updated_teams))
+}
+
+/// adds a team to an existing scoreboard
+pub async fn create_team(
+    scoreboard_id: i32,
+    payload: db::CreateTeamPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // check if the user can perform this action
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // teams are added one after another so they don't end up on the same position
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    // the teams of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    // games of the catalog limit how many teams can play them
+    if let Some(game_id) = scoreboard.game_id {
+        if let Some(game) = Game::get_game(&pool, &game_id).await? {
+            let teams = db::Team::get_teams_by_scoreboard_id_tx(&mut tx, &scoreboard_id).await?;
+
+            if let Some(max_teams) = game.max_teams {
+                if teams.len() as i32 >= max_teams {
+                    return Err(warp::reject::custom(TalliiError::BadRequest(format!(
+                        "{} can't be played with more than {} teams",
+                        game.name, max_teams
+                    ))));
+                }
+            }
+        }
+    }
+
+    let team = db::Team::create_team_tx(&mut tx, &payload, &scoreboard_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&team),
+        StatusCode::CREATED,
+    ))
+}
+
+/// removes a team from its scoreboard
+pub async fn delete_team(
+    team_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the team
+    let team = db::Team::get_team(&pool, &team_id).await?;
+
+    // check if the user can perform this action
+    require_role(&pool, &team.scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // teams are removed one after another with the other changes to the scoreboard
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &team.scoreboard_id).await?;
+
+    // the teams of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    // the match of a tournament is confirmed from the scores of its teams
+    if TournamentMatch::is_played_by_team_tx(&mut tx, &team_id).await? {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the team plays a tournament match that is not confirmed yet",
+        ))));
+    }
+
+    // games of the catalog need a minimum number of teams
+    if let Some(game_id) = scoreboard.game_id {
+        if let Some(game) = Game::get_game(&pool, &game_id).await? {
+            let teams =
+                db::Team::get_teams_by_scoreboard_id_tx(&mut tx, &team.scoreboard_id).await?;
+
+            if teams.len() as i32 <= game.min_teams {
+                return Err(warp::reject::custom(TalliiError::BadRequest(format!(
+                    "{} can't be played with less than {} teams",
+                    game.name, game.min_teams
+                ))));
+            }
+        }
+    }
+
+    // delete the team
+    db::Team::delete_team_tx(&mut tx, &team_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status("team deleted", StatusCode::OK))
+}
+
+/// changes the order of the teams of a scoreboard
+pub async fn update_team_order(
+    scoreboard_id: i32,
+    payload: UpdateTeamOrderPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // a team can only have one position
+    if !payload.team_ids.iter().all_unique() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "a team can only be included once",
+        ))));
+    }
+
+    // check if the user can perform this action
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // teams can't be added or removed while they are being moved
+    Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+
+    let teams = db::Team::get_teams_by_scoreboard_id_tx(&mut tx, &scoreboard_id).await?;
+
+    // every team of the scoreboard has to get a new position
+    let mut updated_teams =
+        db::Team::update_positions_tx(&mut tx, &scoreboard_id, &payload.team_ids).await?;
+
+    // dropping the transaction rolls it back
+    if updated_teams.len() != payload.team_ids.len() || updated_teams.len() != teams.len() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "every team of the scoreboard has to be included",
+        ))));
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    updated_teams.sort_by_key(|team| team.position);
+
+    Ok(warp::reply::json(&updated_teams))
+}
+
+/// adds a user or a guest to a team
+pub async fn add_team_member(
+    team_id: i32,
+    payload: AddTeamMemberPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // a player is either a user or a guest
+    if payload.user_id.is_some() == payload.guest_name.is_some() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "either a user or a guest name has to be set",
+        ))));
+    }
+
+    // get the team
+    let team = db::Team::get_team(&pool, &team_id).await?;
+
+    // check if the user can perform this action
+    require_role(&pool, &team.scoreboard_id, &token.claims.sub, Role::Editor).await?;
+
+    // make sure the user exists
+    if let Some(user_id) = payload.user_id {
+        if users::db::User::get_by_user_id_option(&pool, &user_id)
+            .await?
+            .is_none()
+        {
+            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+                "the user does not exist",
+            ))));
+        }
+    }
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // players are added one after another so a user can't end up on two teams
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &team.scoreboard_id).await?;
+
+    // the players of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    // a user can only play on one team of a scoreboard
+    if let Some(user_id) = payload.user_id {
+        if db::TeamMember::is_playing_tx(&mut tx, &team.scoreboard_id, &user_id).await? {
+            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+                "the user already plays on a team of this scoreboard",
+            ))));
+        }
+    }
+
+    db::TeamMember::add_team_member_tx(&mut tx, &team_id, &payload.user_id, &payload.guest_name)
+        .await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let team_members = db::TeamMember::get_team_members_by_team_id(&pool, &team_id).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&team_members),
+        StatusCode::CREATED,
+    ))
+}
+
+/// removes a player from a team
+pub async fn remove_team_member(
+    team_id: i32,
+    team_member_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the team
+    let team = db::Team::get_team(&pool, &team_id).await?;
+
+    let user_id = db::TeamMember::get_team_member_user_id(&pool, &team_id, &team_member_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    // users can take themselves off a team, otherwise only the people keeping score can
+    if user_id != Some(token.claims.sub) {
+        require_role(&pool, &team.scoreboard_id, &token.claims.sub, Role::Editor).await?;
+    }
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // players are removed one after another with the other changes to the scoreboard
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &team.scoreboard_id).await?;
+
+    // the players of finished scoreboards are frozen
+    scoreboard.require_active()?;
+
+    if !db::TeamMember::remove_team_member_tx(&mut tx, &team_id, &team_member_id).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status(
+        "team member removed",
+        StatusCode::OK,
+    ))
+}
@@ -16,6 +16,13 @@ impl TeamRoutes {
         get_teams(pool.clone())
             .or(get_team(pool.clone()))
             .or(update_team(pool.clone()))
+            .or(update_score(pool.clone()))
+            .or(update_scores(pool.clone()))
+            .or(create_team(pool.clone()))
+            .or(delete_team(pool.clone()))
+            .or(update_team_order(pool.clone()))
+            .or(add_team_member(pool.clone()))
+            .or(remove_team_member(pool.clone()))
     }
 }
 
@@ -25,8 +32,9 @@ pub fn get_team(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "teams" / i32)
         .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::get_team)
 }
 
@@ -37,7 +45,7 @@ pub fn get_teams(
     warp::path!("v1" / "teams")
         .and(warp::get())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::get_teams)
 }
 
@@ -47,8 +55,91 @@ pub fn update_team(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "teams" / i32)
         .and(warp::put())
+        .and(warp::header::optional::<String>("if-match"))
         .and(warp::body::json())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::update_team)
 }
+
+/// adds a delta to the score of a team
+pub fn update_score(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "teams" / i32 / "score")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::update_score)
+}
+
+/// adds deltas to the scores of many teams of a scoreboard
+pub fn update_scores(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "scores")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::update_scores)
+}
+
+/// adds a team to a scoreboard
+pub fn create_team(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "teams")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_team)
+}
+
+/// deletes a specific team
+pub fn delete_team(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "teams" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::delete_team)
+}
+
+/// changes the order of the teams of a scoreboard
+pub fn update_team_order(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "scoreboards" / i32 / "teams" / "order")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::update_team_order)
+}
+
+/// adds a player to a team
+pub fn add_team_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "teams" / i32 / "members")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::add_team_member)
+}
+
+/// removes a player from a team
+pub fn remove_team_member(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "teams" / i32 / "members" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::remove_team_member)
+}
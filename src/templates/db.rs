@@ -0,0 +1,155 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::errors::TalliiError;
+use crate::scoreboards::db::{Visibility, WinCondition};
+use crate::scoreboards::handlers::CreateScoreboardPayload;
+use crate::teams::db::CreateTeamPayload;
+use crate::Result;
+
+/// The settings and teams of a scoreboard that a user saved to create scoreboards from
+#[derive(FromRow, Serialize, Debug)]
+pub struct Template {
+    pub template_id: i32,
+    pub created_by: i32,
+    pub name: String,
+    pub game: String,
+    pub game_id: Option<i32>,
+    pub visibility: Visibility,
+    pub win_condition: WinCondition,
+    pub target_score: Option<i32>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub allow_negative: bool,
+    pub score_step: i32,
+    pub teams: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl Template {
+    /// the scoreboard the template creates
+    pub fn to_payload(&self) -> CreateScoreboardPayload {
+        CreateScoreboardPayload {
+            name: self.name.clone(),
+            game: Some(self.game.clone()),
+            game_id: self.game_id,
+            visibility: self.visibility,
+            win_condition: Some(self.win_condition),
+            target_score: self.target_score,
+            min_score: self.min_score,
+            max_score: self.max_score,
+            allow_negative: Some(self.allow_negative),
+            score_step: Some(self.score_step),
+            teams: self
+                .teams
+                .iter()
+                .map(|name| CreateTeamPayload { name: name.clone() })
+                .collect(),
+        }
+    }
+
+    /// fetches the templates of a user
+    pub async fn get_templates_by_user_id(conn: &PgPool, user_id: &i32) -> Result<Vec<Template>> {
+        sqlx::query_as::<_, Template>(
+            r#"
+                select
+                    *
+                from
+                    scoreboard_templates
+                where
+                    created_by = $1
+                order by
+                    name, template_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a single template of a user
+    pub async fn get_template(
+        conn: &PgPool,
+        template_id: &i32,
+        user_id: &i32,
+    ) -> Result<Option<Template>> {
+        sqlx::query_as::<_, Template>(
+            r#"
+                select
+                    *
+                from
+                    scoreboard_templates
+                where
+                    template_id = $1
+                and
+                    created_by = $2
+            "#,
+        )
+        .bind(template_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// saves the scoreboard of the payload as a template of the user
+    pub async fn create_template(
+        conn: &PgPool,
+        payload: &CreateScoreboardPayload,
+        user_id: &i32,
+    ) -> Result<Template> {
+        let teams: Vec<&str> = payload
+            .teams
+            .iter()
+            .map(|team| team.name.as_str())
+            .collect();
+
+        sqlx::query_as::<_, Template>(
+            r#"
+                insert into
+                    scoreboard_templates (created_by, name, game, game_id, visibility, win_condition, target_score, min_score, max_score, allow_negative, score_step, teams)
+                values
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                returning
+                    *
+            "#,
+        )
+        .bind(user_id)
+        .bind(&payload.name)
+        .bind(&payload.game)
+        .bind(payload.game_id)
+        .bind(payload.visibility)
+        .bind(payload.win_condition.unwrap_or_default())
+        .bind(payload.target_score)
+        .bind(payload.min_score)
+        .bind(payload.max_score)
+        .bind(payload.allow_negative())
+        .bind(payload.score_step())
+        .bind(teams)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// deletes a template of a user, returns false if there is no such template
+    pub async fn delete_template(conn: &PgPool, template_id: &i32, user_id: &i32) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                delete from
+                    scoreboard_templates
+                where
+                    template_id = $1
+                and
+                    created_by = $2
+            "#,
+        )
+        .bind(template_id)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use jsonwebtoken::TokenData;
+use sqlx::PgPool;
+use warp::hyper::StatusCode;
+
+use crate::config::Config;
+use crate::scoreboards::handlers::{
+    apply_catalog_game, create_scoreboard_reply, CreateScoreboardPayload,
+};
+use crate::scoreboards::scoring::validate_rules;
+use crate::users::token::Claims;
+use crate::ResponseResult;
+
+use super::db::Template;
+
+/// saves the settings and teams of a scoreboard as a template of the user
+pub async fn create_template(
+    mut payload: CreateScoreboardPayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // templates are matched with the catalog just like new scoreboards are
+    apply_catalog_game(&pool, &mut payload).await?;
+
+    // make sure the scoring rules make sense
+    validate_rules(payload.min_score, payload.max_score, payload.score_step())?;
+
+    let template = Template::create_template(&pool, &payload, &token.claims.sub).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&template),
+        StatusCode::CREATED,
+    ))
+}
+
+/// gets the templates of the current user
+pub async fn get_my_templates(
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let templates = Template::get_templates_by_user_id(&pool, &token.claims.sub).await?;
+
+    Ok(warp::reply::json(&templates))
+}
+
+/// creates a scoreboard from a template of the current user
+pub async fn create_scoreboard_from_template(
+    template_id: i32,
+    pool: Arc<PgPool>,
+    config: Config,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // users can only use their own templates
+    let template = Template::get_template(&pool, &template_id, &token.claims.sub)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    create_scoreboard_reply(&template.to_payload(), pool, &config, &token.claims.sub).await
+}
+
+/// deletes a template of the current user
+pub async fn delete_template(
+    template_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    if !Template::delete_template(&pool, &template_id, &token.claims.sub).await? {
+        return Err(warp::reject::not_found());
+    }
+
+    Ok(warp::reply::with_status("template deleted", StatusCode::OK))
+}
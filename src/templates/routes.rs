@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::config::Config;
+use crate::wrappers::{with_auth, with_config, with_pool};
+
+use super::handlers;
+
+pub struct TemplateRoutes;
+
+impl TemplateRoutes {
+    /// Init the template routes
+    pub fn init(
+        pool: Arc<PgPool>,
+        config: Config,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        create_template(pool.clone())
+            .or(get_my_templates(pool.clone()))
+            .or(create_scoreboard_from_template(
+                pool.clone(),
+                config.clone(),
+            ))
+            .or(delete_template(pool.clone()))
+    }
+}
+
+/// POST /v1/templates
+pub fn create_template(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "templates")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_template)
+}
+
+/// GET /v1/me/templates
+pub fn get_my_templates(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "templates")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_my_templates)
+}
+
+/// POST /v1/templates/{id}/scoreboards
+pub fn create_scoreboard_from_template(
+    pool: Arc<PgPool>,
+    config: Config,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "templates" / i32 / "scoreboards")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_scoreboard_from_template)
+}
+
+/// DELETE /v1/templates/{id}
+pub fn delete_template(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "templates" / i32)
+        .and(warp::delete())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::delete_template)
+}
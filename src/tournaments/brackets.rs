@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+
+use super::db::{Bracket, Format};
+
+/// A match of a tournament before it is saved. entrants are the indexes of the seeded entrants,
+/// so the first seed is 0.
+#[derive(Debug)]
+pub struct PlannedMatch {
+    pub bracket: Bracket,
+    pub round: i32,
+    pub position: i32,
+    pub entrants: [Option<usize>; 2],
+    pub voids: [bool; 2],
+    /// the index of the match and the slot (1 or 2) the winner moves on to
+    pub winner_to: Option<(usize, i32)>,
+    /// the index of the match and the slot (1 or 2) the loser moves on to
+    pub loser_to: Option<(usize, i32)>,
+}
+
+impl PlannedMatch {
+    fn new(bracket: Bracket, round: i32, position: i32) -> Self {
+        PlannedMatch {
+            bracket,
+            round,
+            position,
+            entrants: [None, None],
+            voids: [false, false],
+            winner_to: None,
+            loser_to: None,
+        }
+    }
+
+    /// a void match has no entrants at all, since both of its slots come from byes
+    pub fn is_void(&self) -> bool {
+        self.voids[0] && self.voids[1]
+    }
+}
+
+/// The matches of a tournament, in the order they can be played
+struct Plan {
+    matches: Vec<PlannedMatch>,
+    indexes: HashMap<(Bracket, i32, i32), usize>,
+}
+
+impl Plan {
+    fn new() -> Self {
+        Plan {
+            matches: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, bracket: Bracket, round: i32, position: i32) -> usize {
+        let index = self.matches.len();
+        self.matches
+            .push(PlannedMatch::new(bracket, round, position));
+        self.indexes.insert((bracket, round, position), index);
+        index
+    }
+
+    fn index(&self, bracket: Bracket, round: i32, position: i32) -> usize {
+        self.indexes[&(bracket, round, position)]
+    }
+
+    fn winner_to(&mut self, from: usize, to: usize, slot: i32) {
+        self.matches[from].winner_to = Some((to, slot));
+    }
+
+    fn loser_to(&mut self, from: usize, to: usize, slot: i32) {
+        self.matches[from].loser_to = Some((to, slot));
+    }
+}
+
+/// Plans the matches of a tournament for a number of seeded entrants
+pub fn plan(format: Format, entrants: usize) -> Vec<PlannedMatch> {
+    match format {
+        Format::SingleElimination => plan_elimination(entrants, false),
+        Format::DoubleElimination => plan_elimination(entrants, true),
+        Format::RoundRobin => plan_round_robin(entrants),
+    }
+}
+
+/// The order of the seeds in the first round of a bracket, so the best seeds meet as late as
+/// possible. a bracket of 8 starts with 1-8, 4-5, 2-7 and 3-6.
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+
+    while order.len() < size {
+        let seeds = order.len() * 2 + 1;
+        order = order
+            .into_iter()
+            .flat_map(|seed| [seed, seeds - seed])
+            .collect();
+    }
+
+    order
+}
+
+/// Plans an elimination bracket. the bracket is filled up to a power of two with byes, which go to
+/// the best seeds. in double elimination the losers play on in a losers bracket, and its winner
+/// meets the winner of the winners bracket in a single grand final.
+fn plan_elimination(entrants: usize, double: bool) -> Vec<PlannedMatch> {
+    let size = entrants.next_power_of_two().max(2);
+    let rounds = size.trailing_zeros() as i32;
+    let mut plan = Plan::new();
+
+    // the winners bracket, where every round halves the matches
+    for round in 1..=rounds {
+        for position in 0..(size >> round) as i32 {
+            plan.add(Bracket::Winners, round, position);
+        }
+    }
+
+    // seed the first round, where the seeds without an entrant are byes
+    for (position, seeds) in seed_order(size).chunks(2).enumerate() {
+        let index = plan.index(Bracket::Winners, 1, position as i32);
+
+        for (slot, seed) in seeds.iter().enumerate() {
+            if *seed <= entrants {
+                plan.matches[index].entrants[slot] = Some(seed - 1);
+            } else {
+                plan.matches[index].voids[slot] = true;
+            }
+        }
+    }
+
+    for round in 1..rounds {
+        for position in 0..(size >> round) as i32 {
+            let from = plan.index(Bracket::Winners, round, position);
+            let to = plan.index(Bracket::Winners, round + 1, position / 2);
+            plan.winner_to(from, to, position % 2 + 1);
+        }
+    }
+
+    if double {
+        plan_losers_bracket(&mut plan, size, rounds);
+    }
+
+    // settle which slots will never get an entrant because of the byes
+    for index in 0..plan.matches.len() {
+        let (voids, winner_to, loser_to) = {
+            let planned = &plan.matches[index];
+            (planned.voids, planned.winner_to, planned.loser_to)
+        };
+
+        // a match with a bye has no loser, and a match of two byes has no winner either
+        if let (Some((to, slot)), true) = (loser_to, voids[0] || voids[1]) {
+            plan.matches[to].voids[slot as usize - 1] = true;
+        }
+
+        if let (Some((to, slot)), true) = (winner_to, voids[0] && voids[1]) {
+            plan.matches[to].voids[slot as usize - 1] = true;
+        }
+    }
+
+    plan.matches
+}
+
+/// Plans the losers bracket and the grand final of a double elimination bracket. the first round
+/// pairs up the losers of the first winners round, after that the rounds alternate between the
+/// losers of the next winners round dropping in and the remaining entrants playing each other.
+/// the losers drop in in reverse order, which keeps early rematches down.
+fn plan_losers_bracket(plan: &mut Plan, size: usize, rounds: i32) {
+    let losers_rounds = 2 * (rounds - 1);
+
+    for round in 1..=losers_rounds {
+        let matches = size >> ((round + 1) / 2 + 1);
+
+        for position in 0..matches as i32 {
+            plan.add(Bracket::Losers, round, position);
+        }
+    }
+
+    let grand_final = plan.add(Bracket::GrandFinal, 1, 0);
+
+    for round in 1..=losers_rounds {
+        let matches = (size >> ((round + 1) / 2 + 1)) as i32;
+
+        for position in 0..matches {
+            let index = plan.index(Bracket::Losers, round, position);
+
+            if round == 1 {
+                // the losers of the first winners round play each other
+                for slot in 1..=2 {
+                    let from = plan.index(Bracket::Winners, 1, position * 2 + slot - 1);
+                    plan.loser_to(from, index, slot);
+                }
+            } else if round % 2 == 0 {
+                // the losers of the next winners round drop in
+                let from = plan.index(Bracket::Losers, round - 1, position);
+                plan.winner_to(from, index, 1);
+
+                let from = plan.index(Bracket::Winners, round / 2 + 1, matches - 1 - position);
+                plan.loser_to(from, index, 2);
+            } else {
+                // the entrants that are left play each other
+                for slot in 1..=2 {
+                    let from = plan.index(Bracket::Losers, round - 1, position * 2 + slot - 1);
+                    plan.winner_to(from, index, slot);
+                }
+            }
+        }
+    }
+
+    let winners_final = plan.index(Bracket::Winners, rounds, 0);
+    plan.winner_to(winners_final, grand_final, 1);
+
+    if losers_rounds == 0 {
+        // with two entrants there is no losers bracket, the final is played again
+        plan.loser_to(winners_final, grand_final, 2);
+    } else {
+        let losers_final = plan.index(Bracket::Losers, losers_rounds, 0);
+        plan.winner_to(losers_final, grand_final, 2);
+    }
+}
+
+/// Plans a round robin where every entrant plays every other entrant once, using the circle
+/// method. with an odd number of entrants one of them sits out every round.
+fn plan_round_robin(entrants: usize) -> Vec<PlannedMatch> {
+    let mut circle: Vec<Option<usize>> = (0..entrants).map(Some).collect();
+    if circle.len() % 2 == 1 {
+        circle.push(None);
+    }
+
+    let mut plan = Plan::new();
+    let size = circle.len();
+
+    for round in 1..size as i32 {
+        let mut position = 0;
+
+        for i in 0..size / 2 {
+            if let (Some(first), Some(second)) = (circle[i], circle[size - 1 - i]) {
+                let index = plan.add(Bracket::RoundRobin, round, position);
+                plan.matches[index].entrants = [Some(first), Some(second)];
+                position += 1;
+            }
+        }
+
+        // the first entrant stays in place while the others rotate
+        circle[1..].rotate_right(1);
+    }
+
+    plan.matches
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn find(
+        matches: &[PlannedMatch],
+        bracket: Bracket,
+        round: i32,
+        position: i32,
+    ) -> &PlannedMatch {
+        matches
+            .iter()
+            .find(|planned| {
+                planned.bracket == bracket && planned.round == round && planned.position == position
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn seeds_meet_as_late_as_possible() {
+        assert_eq!(seed_order(2), vec![1, 2]);
+        assert_eq!(seed_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn byes_go_to_the_best_seeds() {
+        let matches = plan(Format::SingleElimination, 5);
+
+        assert_eq!(matches.len(), 7);
+        assert!(matches.iter().all(|planned| !planned.is_void()));
+
+        let first_round: Vec<&PlannedMatch> = matches
+            .iter()
+            .filter(|planned| planned.round == 1)
+            .collect();
+
+        assert_eq!(first_round[0].entrants, [Some(0), None]);
+        assert_eq!(first_round[0].voids, [false, true]);
+        assert_eq!(first_round[1].entrants, [Some(3), Some(4)]);
+        assert_eq!(first_round[1].voids, [false, false]);
+        assert_eq!(first_round[2].entrants, [Some(1), None]);
+        assert_eq!(first_round[3].entrants, [Some(2), None]);
+    }
+
+    #[test]
+    fn winners_move_on_to_the_next_round() {
+        let matches = plan(Format::SingleElimination, 4);
+
+        let final_index = matches
+            .iter()
+            .position(|planned| planned.round == 2)
+            .unwrap();
+
+        assert_eq!(matches[0].winner_to, Some((final_index, 1)));
+        assert_eq!(matches[1].winner_to, Some((final_index, 2)));
+        assert_eq!(matches[final_index].winner_to, None);
+        assert!(matches.iter().all(|planned| planned.loser_to.is_none()));
+    }
+
+    #[test]
+    fn byes_void_the_losers_bracket() {
+        let matches = plan(Format::DoubleElimination, 5);
+
+        // 7 winners matches, 6 losers matches and the grand final
+        assert_eq!(matches.len(), 14);
+
+        // the losers of two byes never play, so neither does the winner of their match
+        let losers_match = find(&matches, Bracket::Losers, 1, 1);
+        assert!(losers_match.is_void());
+        assert!(find(&matches, Bracket::Losers, 2, 1).voids[0]);
+
+        let losers_match = find(&matches, Bracket::Losers, 1, 0);
+        assert_eq!(losers_match.voids, [true, false]);
+    }
+
+    #[test]
+    fn two_entrants_play_the_final_again() {
+        let matches = plan(Format::DoubleElimination, 2);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].winner_to, Some((1, 1)));
+        assert_eq!(matches[0].loser_to, Some((1, 2)));
+        assert_eq!(matches[1].bracket, Bracket::GrandFinal);
+    }
+
+    #[test]
+    fn round_robin_pairs_every_entrant_once() {
+        for entrants in 2..=7 {
+            let matches = plan(Format::RoundRobin, entrants);
+
+            assert_eq!(matches.len(), entrants * (entrants - 1) / 2);
+
+            let mut pairs = HashSet::new();
+            let mut played = HashSet::new();
+
+            for planned in matches.iter() {
+                let first = planned.entrants[0].unwrap();
+                let second = planned.entrants[1].unwrap();
+
+                assert!(pairs.insert((first.min(second), first.max(second))));
+
+                // nobody plays twice in the same round
+                assert!(played.insert((planned.round, first)));
+                assert!(played.insert((planned.round, second)));
+            }
+        }
+    }
+}
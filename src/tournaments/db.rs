@@ -0,0 +1,648 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+use super::brackets::PlannedMatch;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "tournament_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    SingleElimination,
+    DoubleElimination,
+    RoundRobin,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "tournament_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TournamentStatus {
+    Active,
+    Finished,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(type_name = "tournament_bracket", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Bracket {
+    Winners,
+    Losers,
+    GrandFinal,
+    RoundRobin,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "tournament_match_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MatchStatus {
+    /// waiting for its entrants
+    Pending,
+    /// its scoreboard is being played
+    Ready,
+    Completed,
+    /// both of its slots are byes, so it is never played
+    Void,
+}
+
+/// A tournament where every match is played on its own scoreboard
+#[derive(FromRow, Serialize, Debug)]
+pub struct Tournament {
+    pub tournament_id: i32,
+    pub created_by: i32,
+    pub name: String,
+    pub game: String,
+    pub game_id: Option<i32>,
+    pub format: Format,
+    pub status: TournamentStatus,
+    pub winner_entrant_id: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+/// A team or player taking part in a tournament
+#[derive(FromRow, Serialize, Debug)]
+pub struct Entrant {
+    pub entrant_id: i32,
+    pub tournament_id: i32,
+    pub name: String,
+    pub seed: i32,
+}
+
+/// A match of a tournament along with the matches its winner and loser move on to
+#[derive(FromRow, Serialize, Debug)]
+pub struct TournamentMatch {
+    pub match_id: i32,
+    pub tournament_id: i32,
+    pub bracket: Bracket,
+    pub round: i32,
+    pub position: i32,
+    pub entrant1_id: Option<i32>,
+    pub entrant2_id: Option<i32>,
+    pub slot1_void: bool,
+    pub slot2_void: bool,
+    pub scoreboard_id: Option<i32>,
+    pub team1_id: Option<i32>,
+    pub team2_id: Option<i32>,
+    pub status: MatchStatus,
+    pub winner_entrant_id: Option<i32>,
+    pub score1: Option<i32>,
+    pub score2: Option<i32>,
+    pub winner_match_id: Option<i32>,
+    pub winner_slot: Option<i32>,
+    pub loser_match_id: Option<i32>,
+    pub loser_slot: Option<i32>,
+    pub completed_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+}
+
+impl Tournament {
+    /// creates a tournament
+    pub async fn create_tournament_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        name: &str,
+        game: &str,
+        game_id: &Option<i32>,
+        format: &Format,
+        user_id: &i32,
+    ) -> Result<Tournament> {
+        sqlx::query_as::<_, Tournament>(
+            r#"
+                insert into
+                    tournaments (name, game, game_id, format, created_by)
+                values
+                    ($1, $2, $3, $4, $5)
+                returning
+                    *
+            "#,
+        )
+        .bind(name)
+        .bind(game)
+        .bind(game_id)
+        .bind(format)
+        .bind(user_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a tournament
+    pub async fn get_tournament(conn: &PgPool, tournament_id: &i32) -> Result<Option<Tournament>> {
+        sqlx::query_as::<_, Tournament>(
+            r#"
+                select
+                    *
+                from
+                    tournaments
+                where
+                    tournament_id = $1
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if the user created the tournament, plays in one of its matches or can see the
+    /// scoreboard of one of its matches
+    pub async fn is_visible_to(
+        conn: &PgPool,
+        tournament: &Tournament,
+        user_id: &i32,
+    ) -> Result<bool> {
+        if tournament.created_by == *user_id {
+            return Ok(true);
+        }
+
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select
+                        1
+                    from
+                        tournament_matches tm
+                    inner join
+                        team_members p
+                    on
+                        p.team_id = tm.team1_id or p.team_id = tm.team2_id
+                    where
+                        tm.tournament_id = $1
+                    and
+                        p.user_id = $2
+                ) or exists (
+                    select
+                        1
+                    from
+                        tournament_matches tm
+                    inner join
+                        scoreboards s
+                    on
+                        s.scoreboard_id = tm.scoreboard_id
+                    where
+                        tm.tournament_id = $1
+                    and (
+                        s.visibility <> 'private' or exists (
+                            select 1 from scoreboard_members m where m.scoreboard_id = s.scoreboard_id and m.user_id = $2
+                        )
+                    )
+                )
+            "#,
+        )
+        .bind(tournament.tournament_id)
+        .bind(user_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// locks a tournament until the transaction ends, so the results of its matches are confirmed
+    /// one after another
+    pub async fn lock_tournament_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+    ) -> Result<Option<Tournament>> {
+        sqlx::query_as::<_, Tournament>(
+            r#"
+                select
+                    *
+                from
+                    tournaments
+                where
+                    tournament_id = $1
+                for update
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// finishes a tournament with its winner
+    pub async fn finish_tournament_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+        winner_entrant_id: &Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                update
+                    tournaments
+                set
+                    status = 'finished',
+                    winner_entrant_id = $2
+                where
+                    tournament_id = $1
+            "#,
+        )
+        .bind(tournament_id)
+        .bind(winner_entrant_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl Entrant {
+    /// creates the entrants of a tournament, seeded in the order they are passed
+    pub async fn create_entrants_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+        names: &[String],
+    ) -> Result<Vec<Entrant>> {
+        sqlx::query_as::<_, Entrant>(
+            r#"
+                insert into
+                    tournament_entrants (tournament_id, name, seed)
+                select
+                    $1, name, seed
+                from
+                    unnest($2::text[]) with ordinality as entrants(name, seed)
+                returning
+                    *
+            "#,
+        )
+        .bind(tournament_id)
+        .bind(names)
+        .fetch_all(tx)
+        .await
+        .map(|mut entrants| {
+            entrants.sort_by_key(|entrant| entrant.seed);
+            entrants
+        })
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the entrants of a tournament by their seed
+    pub async fn get_entrants(conn: &PgPool, tournament_id: &i32) -> Result<Vec<Entrant>> {
+        sqlx::query_as::<_, Entrant>(
+            r#"
+                select
+                    *
+                from
+                    tournament_entrants
+                where
+                    tournament_id = $1
+                order by
+                    seed
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the entrants of a tournament by their id
+    pub async fn get_entrants_by_ids_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        entrant_ids: &[i32],
+    ) -> Result<Vec<Entrant>> {
+        sqlx::query_as::<_, Entrant>(
+            r#"
+                select
+                    *
+                from
+                    tournament_entrants
+                where
+                    entrant_id = any($1)
+            "#,
+        )
+        .bind(entrant_ids)
+        .fetch_all(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches the entrant that won the most matches of a round robin, where a draw counts as
+    /// half a win. ties go to the bigger score difference over all matches and then to the
+    /// better seed.
+    pub async fn get_round_robin_winner_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+    ) -> Result<Option<i32>> {
+        sqlx::query_scalar::<_, i32>(
+            r#"
+                select
+                    e.entrant_id
+                from
+                    tournament_entrants e
+                left join
+                    tournament_matches m
+                on
+                    m.status = 'completed' and e.entrant_id in (m.entrant1_id, m.entrant2_id)
+                where
+                    e.tournament_id = $1
+                group by
+                    e.entrant_id
+                order by
+                    sum(case
+                        when m.winner_entrant_id = e.entrant_id then 2
+                        when m.match_id is not null and m.winner_entrant_id is null then 1
+                        else 0
+                    end) desc,
+                    sum(case
+                        when m.winner_entrant_id = e.entrant_id then coalesce(abs(m.score1 - m.score2), 0)
+                        when m.winner_entrant_id is not null then -coalesce(abs(m.score1 - m.score2), 0)
+                        else 0
+                    end) desc,
+                    e.seed
+                limit 1
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl TournamentMatch {
+    /// a match can be settled once every slot that isn't a bye has its entrant
+    pub fn is_settled(&self) -> bool {
+        (self.entrant1_id.is_some() || self.slot1_void)
+            && (self.entrant2_id.is_some() || self.slot2_void)
+    }
+
+    /// creates a planned match with its first entrants. matches where both slots are byes are
+    /// void right away.
+    pub async fn create_match_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+        planned: &PlannedMatch,
+        entrant_ids: [Option<i32>; 2],
+    ) -> Result<TournamentMatch> {
+        let status = if planned.is_void() {
+            MatchStatus::Void
+        } else {
+            MatchStatus::Pending
+        };
+
+        sqlx::query_as::<_, TournamentMatch>(
+            r#"
+                insert into
+                    tournament_matches (tournament_id, bracket, round, position, entrant1_id, entrant2_id, slot1_void, slot2_void, status)
+                values
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                returning
+                    *
+            "#,
+        )
+        .bind(tournament_id)
+        .bind(planned.bracket)
+        .bind(planned.round)
+        .bind(planned.position)
+        .bind(entrant_ids[0])
+        .bind(entrant_ids[1])
+        .bind(planned.voids[0])
+        .bind(planned.voids[1])
+        .bind(status)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// links a match to the matches its winner and loser move on to
+    pub async fn set_next_matches_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        match_id: &i32,
+        winner_to: Option<(i32, i32)>,
+        loser_to: Option<(i32, i32)>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                update
+                    tournament_matches
+                set
+                    winner_match_id = $2,
+                    winner_slot = $3,
+                    loser_match_id = $4,
+                    loser_slot = $5
+                where
+                    match_id = $1
+            "#,
+        )
+        .bind(match_id)
+        .bind(winner_to.map(|(match_id, _)| match_id))
+        .bind(winner_to.map(|(_, slot)| slot))
+        .bind(loser_to.map(|(match_id, _)| match_id))
+        .bind(loser_to.map(|(_, slot)| slot))
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// fetches the matches of a tournament in the order of the bracket
+    pub async fn get_matches(conn: &PgPool, tournament_id: &i32) -> Result<Vec<TournamentMatch>> {
+        sqlx::query_as::<_, TournamentMatch>(
+            r#"
+                select
+                    *
+                from
+                    tournament_matches
+                where
+                    tournament_id = $1
+                order by
+                    bracket, round, position
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// fetches a single match of a tournament
+    pub async fn get_match_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+        match_id: &i32,
+    ) -> Result<Option<TournamentMatch>> {
+        sqlx::query_as::<_, TournamentMatch>(
+            r#"
+                select
+                    *
+                from
+                    tournament_matches
+                where
+                    tournament_id = $1
+                and
+                    match_id = $2
+            "#,
+        )
+        .bind(tournament_id)
+        .bind(match_id)
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// puts an entrant in a slot of a match
+    pub async fn set_entrant_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        match_id: &i32,
+        slot: &i32,
+        entrant_id: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                update
+                    tournament_matches
+                set
+                    entrant1_id = case when $2 = 1 then $3 else entrant1_id end,
+                    entrant2_id = case when $2 = 2 then $3 else entrant2_id end
+                where
+                    match_id = $1
+            "#,
+        )
+        .bind(match_id)
+        .bind(slot)
+        .bind(entrant_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// starts a match on its scoreboard
+    pub async fn start_match_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        match_id: &i32,
+        scoreboard_id: &i32,
+        team1_id: &i32,
+        team2_id: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                update
+                    tournament_matches
+                set
+                    scoreboard_id = $2,
+                    team1_id = $3,
+                    team2_id = $4,
+                    status = 'ready'
+                where
+                    match_id = $1
+            "#,
+        )
+        .bind(match_id)
+        .bind(scoreboard_id)
+        .bind(team1_id)
+        .bind(team2_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// completes a match with its winner, which is left out for a draw
+    pub async fn complete_match_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        match_id: &i32,
+        winner_entrant_id: &Option<i32>,
+        scores: &Option<(i32, i32)>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                update
+                    tournament_matches
+                set
+                    winner_entrant_id = $2,
+                    score1 = $3,
+                    score2 = $4,
+                    status = 'completed',
+                    completed_at = now()
+                where
+                    match_id = $1
+            "#,
+        )
+        .bind(match_id)
+        .bind(winner_entrant_id)
+        .bind(scores.map(|(score1, _)| score1))
+        .bind(scores.map(|(_, score2)| score2))
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// counts the matches of a tournament that still have to be played
+    pub async fn count_open_matches_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        tournament_id: &i32,
+    ) -> Result<i64> {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+                select
+                    count(*)
+                from
+                    tournament_matches
+                where
+                    tournament_id = $1
+                and
+                    status in ('pending', 'ready')
+            "#,
+        )
+        .bind(tournament_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if a tournament match that is not confirmed yet is played on the scoreboard
+    pub async fn is_played_on_scoreboard_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        scoreboard_id: &i32,
+    ) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select
+                        1
+                    from
+                        tournament_matches
+                    where
+                        scoreboard_id = $1
+                    and
+                        status = 'ready'
+                )
+            "#,
+        )
+        .bind(scoreboard_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// checks if a tournament match that is not confirmed yet is played by the team
+    pub async fn is_played_by_team_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        team_id: &i32,
+    ) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+                select exists (
+                    select
+                        1
+                    from
+                        tournament_matches
+                    where
+                        (team1_id = $1 or team2_id = $1)
+                    and
+                        status = 'ready'
+                )
+            "#,
+        )
+        .bind(team_id)
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
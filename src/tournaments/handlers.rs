@@ -0,0 +1,536 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use futures::future;
+use itertools::Itertools;
+use jsonwebtoken::TokenData;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use validator::Validate;
+use warp::hyper::StatusCode;
+
+use crate::config::Config;
+use crate::errors::TalliiError;
+use crate::members::db::Role;
+use crate::members::permissions::require_role;
+use crate::scoreboards::db::{Scoreboard, Status, Visibility, WinCondition};
+use crate::scoreboards::handlers::{
+    apply_catalog_game, create_scoreboard_with_teams_tx, require_verified_email,
+    CreateScoreboardPayload,
+};
+use crate::teams::db::{CreateTeamPayload, Team};
+use crate::users::token::Claims;
+use crate::{ResponseResult, Result};
+
+use super::brackets;
+use super::db::{Bracket, Entrant, Format, MatchStatus, Tournament, TournamentMatch};
+
+#[derive(Deserialize, Validate)]
+pub struct CreateTournamentPayload {
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub game: Option<String>,
+    pub game_id: Option<i32>,
+    pub format: Format,
+    #[validate(length(min = 2, max = 64))]
+    pub entrants: Vec<CreateEntrantPayload>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CreateEntrantPayload {
+    pub name: String,
+    pub seed: Option<i32>,
+}
+
+impl CreateTournamentPayload {
+    /// the names of the entrants by their seed. either every entrant has a seed from 1 on, or
+    /// none of them has one and they are seeded in the order they are listed.
+    fn seeded_names(&self) -> Result<Vec<String>> {
+        let seeded = self
+            .entrants
+            .iter()
+            .filter(|entrant| entrant.seed.is_some())
+            .count();
+
+        if seeded == 0 {
+            return Ok(self
+                .entrants
+                .iter()
+                .map(|entrant| entrant.name.clone())
+                .collect());
+        }
+
+        if seeded != self.entrants.len() {
+            return Err(TalliiError::BadRequest(String::from(
+                "either all entrants have a seed or none of them",
+            )));
+        }
+
+        let entrants: Vec<&CreateEntrantPayload> = self
+            .entrants
+            .iter()
+            .sorted_by_key(|entrant| entrant.seed)
+            .collect();
+
+        for (index, entrant) in entrants.iter().enumerate() {
+            if entrant.seed != Some(index as i32 + 1) {
+                return Err(TalliiError::BadRequest(String::from(
+                    "the seeds have to go from 1 up to the number of entrants",
+                )));
+            }
+        }
+
+        Ok(entrants
+            .into_iter()
+            .map(|entrant| entrant.name.clone())
+            .collect())
+    }
+}
+
+/// The matches of one round of a bracket
+#[derive(Serialize)]
+pub struct BracketRound {
+    pub bracket: Bracket,
+    pub round: i32,
+    pub matches: Vec<TournamentMatch>,
+}
+
+#[derive(Serialize)]
+pub struct BracketResponse {
+    #[serde(flatten)]
+    pub tournament: Tournament,
+    pub entrants: Vec<Entrant>,
+    pub rounds: Vec<BracketRound>,
+}
+
+/// gets a tournament along with its entrants and all of its matches, round by round
+async fn get_bracket_response(pool: &PgPool, tournament: Tournament) -> Result<BracketResponse> {
+    let (entrants, matches) = future::try_join(
+        Entrant::get_entrants(pool, &tournament.tournament_id),
+        TournamentMatch::get_matches(pool, &tournament.tournament_id),
+    )
+    .await?;
+
+    // the matches are ordered by bracket and round already
+    let rounds = matches
+        .into_iter()
+        .group_by(|tournament_match| (tournament_match.bracket, tournament_match.round))
+        .into_iter()
+        .map(|((bracket, round), matches)| BracketRound {
+            bracket,
+            round,
+            matches: matches.collect(),
+        })
+        .collect();
+
+    Ok(BracketResponse {
+        tournament,
+        entrants,
+        rounds,
+    })
+}
+
+/// the scoreboard a match between two entrants is played on. it is unlisted, so the players can
+/// follow it with its id.
+fn match_scoreboard_payload(
+    name: String,
+    game: Option<String>,
+    game_id: Option<i32>,
+    entrants: [&str; 2],
+) -> CreateScoreboardPayload {
+    CreateScoreboardPayload {
+        name,
+        game,
+        game_id,
+        visibility: Visibility::Unlisted,
+        win_condition: None,
+        target_score: None,
+        min_score: None,
+        max_score: None,
+        allow_negative: None,
+        score_step: None,
+        teams: entrants
+            .iter()
+            .map(|name| CreateTeamPayload {
+                name: String::from(*name),
+            })
+            .collect(),
+    }
+}
+
+/// creates the scoreboard of a match whose entrants are known, owned by the creator of the
+/// tournament
+async fn start_match_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    pool: &PgPool,
+    tournament: &Tournament,
+    tournament_match: &TournamentMatch,
+    entrant_ids: [i32; 2],
+) -> Result<()> {
+    let entrants = Entrant::get_entrants_by_ids_tx(tx, &entrant_ids).await?;
+    let name = |entrant_id: i32| {
+        entrants
+            .iter()
+            .find(|entrant| entrant.entrant_id == entrant_id)
+            .map(|entrant| entrant.name.as_str())
+            .unwrap_or_default()
+    };
+
+    // match scoreboards get the settings of the game like any other scoreboard
+    let entrant_names = [name(entrant_ids[0]), name(entrant_ids[1])];
+    let mut payload = match_scoreboard_payload(
+        format!(
+            "{}: {} vs {}",
+            tournament.name, entrant_names[0], entrant_names[1]
+        ),
+        Some(tournament.game.clone()),
+        tournament.game_id,
+        entrant_names,
+    );
+    apply_catalog_game(pool, &mut payload).await?;
+
+    let (scoreboard, teams) =
+        create_scoreboard_with_teams_tx(tx, &payload, &tournament.created_by).await?;
+
+    let team_id = |position: i32| {
+        teams
+            .iter()
+            .find(|team| team.position == position)
+            .map(|team| team.team_id)
+            .ok_or_else(|| {
+                TalliiError::InternalServerError(String::from("a team of the match is missing"))
+            })
+    };
+
+    TournamentMatch::start_match_tx(
+        tx,
+        &tournament_match.match_id,
+        &scoreboard.scoreboard_id,
+        &team_id(0)?,
+        &team_id(1)?,
+    )
+    .await
+}
+
+/// completes a match and moves its winner and loser on to their next matches. returns the
+/// matches that got a new entrant.
+async fn complete_match_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    tournament: &Tournament,
+    tournament_match: &TournamentMatch,
+    winner: Option<i32>,
+    loser: Option<i32>,
+    scores: Option<(i32, i32)>,
+) -> Result<Vec<i32>> {
+    TournamentMatch::complete_match_tx(tx, &tournament_match.match_id, &winner, &scores).await?;
+
+    let mut next_matches = Vec::new();
+
+    let moves = [
+        (
+            winner,
+            tournament_match.winner_match_id,
+            tournament_match.winner_slot,
+        ),
+        (
+            loser,
+            tournament_match.loser_match_id,
+            tournament_match.loser_slot,
+        ),
+    ];
+
+    for (entrant_id, match_id, slot) in moves {
+        if let (Some(entrant_id), Some(match_id), Some(slot)) = (entrant_id, match_id, slot) {
+            TournamentMatch::set_entrant_tx(tx, &match_id, &slot, &entrant_id).await?;
+            next_matches.push(match_id);
+        }
+    }
+
+    if tournament_match.bracket == Bracket::RoundRobin {
+        // a round robin is over once all of its matches are played
+        if TournamentMatch::count_open_matches_tx(tx, &tournament.tournament_id).await? == 0 {
+            let winner = Entrant::get_round_robin_winner_tx(tx, &tournament.tournament_id).await?;
+            Tournament::finish_tournament_tx(tx, &tournament.tournament_id, &winner).await?;
+        }
+    } else if tournament_match.winner_match_id.is_none() {
+        // the winner of the final wins the tournament
+        Tournament::finish_tournament_tx(tx, &tournament.tournament_id, &winner).await?;
+    }
+
+    Ok(next_matches)
+}
+
+/// settles the matches once all of their entrants are known. a match with two entrants gets its
+/// scoreboard, an entrant without an opponent because of a bye moves on right away, which can
+/// settle the matches after it as well.
+async fn settle_matches_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    pool: &PgPool,
+    tournament: &Tournament,
+    match_ids: Vec<i32>,
+) -> Result<()> {
+    let mut queue = VecDeque::from(match_ids);
+
+    while let Some(match_id) = queue.pop_front() {
+        let tournament_match =
+            match TournamentMatch::get_match_tx(tx, &tournament.tournament_id, &match_id).await? {
+                Some(tournament_match) => tournament_match,
+                None => continue,
+            };
+
+        if tournament_match.status != MatchStatus::Pending || !tournament_match.is_settled() {
+            continue;
+        }
+
+        match (tournament_match.entrant1_id, tournament_match.entrant2_id) {
+            (Some(entrant1_id), Some(entrant2_id)) => {
+                start_match_tx(
+                    tx,
+                    pool,
+                    tournament,
+                    &tournament_match,
+                    [entrant1_id, entrant2_id],
+                )
+                .await?
+            }
+            (Some(entrant_id), None) | (None, Some(entrant_id)) => {
+                let next_matches = complete_match_tx(
+                    tx,
+                    tournament,
+                    &tournament_match,
+                    Some(entrant_id),
+                    None,
+                    None,
+                )
+                .await?;
+                queue.extend(next_matches);
+            }
+            // matches of two byes are void from the start
+            (None, None) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// creates a tournament with its entrants and the matches of its format. the matches that can be
+/// played right away get their scoreboards.
+pub async fn create_tournament(
+    payload: CreateTournamentPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    let names = payload.seeded_names()?;
+
+    // every match is a scoreboard of the user
+    require_verified_email(&pool, &config, &token.claims.sub).await?;
+
+    // matches are played by two teams, which the game has to allow
+    let mut sample = match_scoreboard_payload(
+        payload.name.clone(),
+        payload.game.clone(),
+        payload.game_id,
+        [&names[0], &names[1]],
+    );
+    apply_catalog_game(&pool, &mut sample).await?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let tournament = Tournament::create_tournament_tx(
+        &mut tx,
+        &payload.name,
+        sample.game.as_deref().unwrap_or_default(),
+        &sample.game_id,
+        &payload.format,
+        &token.claims.sub,
+    )
+    .await?;
+
+    let entrants = Entrant::create_entrants_tx(&mut tx, &tournament.tournament_id, &names).await?;
+
+    // create the planned matches before linking them to the matches they lead to
+    let planned_matches = brackets::plan(payload.format, entrants.len());
+    let mut match_ids = Vec::with_capacity(planned_matches.len());
+
+    for planned in planned_matches.iter() {
+        let entrant_ids = planned
+            .entrants
+            .map(|entrant| entrant.map(|index| entrants[index].entrant_id));
+
+        let tournament_match = TournamentMatch::create_match_tx(
+            &mut tx,
+            &tournament.tournament_id,
+            planned,
+            entrant_ids,
+        )
+        .await?;
+
+        match_ids.push(tournament_match.match_id);
+    }
+
+    for (planned, match_id) in planned_matches.iter().zip(match_ids.iter()) {
+        if planned.winner_to.is_none() && planned.loser_to.is_none() {
+            continue;
+        }
+
+        TournamentMatch::set_next_matches_tx(
+            &mut tx,
+            match_id,
+            planned
+                .winner_to
+                .map(|(index, slot)| (match_ids[index], slot)),
+            planned
+                .loser_to
+                .map(|(index, slot)| (match_ids[index], slot)),
+        )
+        .await?;
+    }
+
+    // start the first matches and move the entrants with a bye on
+    settle_matches_tx(&mut tx, &pool, &tournament, match_ids).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let tournament = Tournament::get_tournament(&pool, &tournament.tournament_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+    let response = get_bracket_response(&pool, tournament).await?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&response),
+        StatusCode::CREATED,
+    ))
+}
+
+/// gets a tournament with its entrants and all of its matches
+pub async fn get_bracket(
+    tournament_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let tournament = Tournament::get_tournament(&pool, &tournament_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    // the bracket is shown to the people that can follow its matches, the others can't tell it
+    // exists
+    if !Tournament::is_visible_to(&pool, &tournament, &token.claims.sub).await? {
+        return Err(warp::reject::custom(TalliiError::NotFound));
+    }
+
+    let response = get_bracket_response(&pool, tournament).await?;
+
+    Ok(warp::reply::json(&response))
+}
+
+/// confirms the result of a match from the scores of its scoreboard. the team with the better
+/// score moves on, and its next match gets its scoreboard once both entrants are known. only the
+/// owners of the match scoreboard can confirm its result.
+pub async fn confirm_match(
+    tournament_id: i32,
+    match_id: i32,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // results of the same tournament are confirmed one after another
+    let tournament = Tournament::lock_tournament_tx(&mut tx, &tournament_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    let tournament_match = TournamentMatch::get_match_tx(&mut tx, &tournament_id, &match_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+
+    if tournament_match.status != MatchStatus::Ready {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the match is not being played",
+        ))));
+    }
+
+    let (scoreboard_id, team1_id, team2_id) = match (
+        tournament_match.scoreboard_id,
+        tournament_match.team1_id,
+        tournament_match.team2_id,
+    ) {
+        (Some(scoreboard_id), Some(team1_id), Some(team2_id)) => {
+            (scoreboard_id, team1_id, team2_id)
+        }
+        _ => {
+            return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+                "the scoreboard or the teams of the match were deleted",
+            ))))
+        }
+    };
+
+    require_role(&pool, &scoreboard_id, &token.claims.sub, Role::Owner).await?;
+
+    let scoreboard = Scoreboard::lock_scoreboard_tx(&mut tx, &scoreboard_id).await?;
+    let team1 = Team::get_team_tx(&mut tx, &team1_id).await?;
+    let team2 = Team::get_team_tx(&mut tx, &team2_id).await?;
+
+    let first_wins = match scoreboard.win_condition {
+        WinCondition::Highest => team1.score > team2.score,
+        WinCondition::Lowest => team1.score < team2.score,
+    };
+
+    let (winner, loser) = match (tournament_match.entrant1_id, tournament_match.entrant2_id) {
+        _ if team1.score == team2.score => (None, None),
+        (entrant1_id, entrant2_id) if first_wins => (entrant1_id, entrant2_id),
+        (entrant1_id, entrant2_id) => (entrant2_id, entrant1_id),
+    };
+
+    // only a round robin can end in a draw, an elimination match needs a winner
+    if winner.is_none() && tournament_match.bracket != Bracket::RoundRobin {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "the match is tied, it needs a winner",
+        ))));
+    }
+
+    // the scores of a confirmed match are frozen
+    if scoreboard.status == Status::Active {
+        Scoreboard::finish_scoreboard_tx(&mut tx, &scoreboard).await?;
+    }
+
+    let next_matches = complete_match_tx(
+        &mut tx,
+        &tournament,
+        &tournament_match,
+        winner,
+        loser,
+        Some((team1.score, team2.score)),
+    )
+    .await?;
+
+    settle_matches_tx(&mut tx, &pool, &tournament, next_matches).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let tournament = Tournament::get_tournament(&pool, &tournament_id)
+        .await?
+        .ok_or_else(warp::reject::not_found)?;
+    let response = get_bracket_response(&pool, tournament).await?;
+
+    Ok(warp::reply::json(&response))
+}
@@ -0,0 +1,4 @@
+pub mod brackets;
+pub mod db;
+pub mod handlers;
+pub mod routes;
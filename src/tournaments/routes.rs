@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use warp::Filter;
+
+use crate::config::Config;
+use crate::wrappers::{with_auth, with_config, with_pool};
+
+use super::handlers;
+
+pub struct TournamentRoutes;
+
+impl TournamentRoutes {
+    /// Init the tournament routes
+    pub fn init(
+        pool: Arc<PgPool>,
+        config: Config,
+    ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        create_tournament(pool.clone(), config.clone())
+            .or(get_bracket(pool.clone()))
+            .or(confirm_match(pool.clone()))
+    }
+}
+
+/// POST /v1/tournaments
+pub fn create_tournament(
+    pool: Arc<PgPool>,
+    config: Config,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "tournaments")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::create_tournament)
+}
+
+/// GET /v1/tournaments/{id}/bracket
+pub fn get_bracket(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "tournaments" / i32 / "bracket")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::get_bracket)
+}
+
+/// POST /v1/tournaments/{id}/matches/{match_id}/confirm
+pub fn confirm_match(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "tournaments" / i32 / "matches" / i32 / "confirm")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::confirm_match)
+}
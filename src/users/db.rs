@@ -1,4 +1,4 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 use crate::errors::TalliiError;
 use crate::Result;
@@ -12,6 +12,7 @@ pub struct User {
     pub password: String,
     pub avatar_background: String,
     pub avatar_emoji: String,
+    pub email_verified_at: Option<chrono::DateTime<chrono::offset::Utc>>,
     pub created_at: chrono::DateTime<chrono::offset::Utc>,
 }
 
@@ -99,6 +100,24 @@ impl User {
         .map_err(|e| TalliiError::DatabaseError(e.to_string()))
     }
 
+    /// Gets many users by their ids
+    pub async fn get_by_user_ids(conn: &PgPool, user_ids: &[i32]) -> Result<Vec<User>> {
+        sqlx::query_as::<_, User>(
+            r#"
+            select
+                *
+            from
+                users
+            where
+                users.user_id = any($1)
+        "#,
+        )
+        .bind(user_ids)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
     /// Creates an email
     pub async fn create_user(
         conn: &PgPool,
@@ -159,6 +178,130 @@ impl User {
         Ok(user)
     }
 
+    /// updates the password hash of a user
+    pub async fn update_password(conn: &PgPool, user_id: &i32, hash: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                users
+            set
+                password = $1
+            where
+                user_id = $2
+        "#,
+        )
+        .bind(hash)
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// updates the password hash of a user in a transaction
+    pub async fn update_password_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &i32,
+        hash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                users
+            set
+                password = $1
+            where
+                user_id = $2
+        "#,
+        )
+        .bind(hash)
+        .bind(user_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// marks the email of a user as verified as long as it has not changed since the
+    /// verification was sent
+    pub async fn verify_email_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &i32,
+        email: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            update
+                users
+            set
+                email_verified_at = now()
+            where
+                user_id = $1
+            and
+                email = $2
+        "#,
+        )
+        .bind(user_id)
+        .bind(email)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// updates the email of a user in a transaction and marks it as verified
+    pub async fn update_email_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &i32,
+        email: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                users
+            set
+                email = $1,
+                email_verified_at = now()
+            where
+                user_id = $2
+        "#,
+        )
+        .bind(email)
+        .bind(user_id)
+        .execute(tx)
+        .await
+        .map_err(|e| match e {
+            // the email is unique so someone could have taken it since the change was requested
+            sqlx::Error::Database(ref db_error) if db_error.code().as_deref() == Some("23505") => {
+                TalliiError::UserEmailTaken
+            }
+            _ => TalliiError::DatabaseError(e.to_string()),
+        })?;
+
+        Ok(())
+    }
+
+    /// deletes a user along with everything they created
+    pub async fn delete_user(conn: &PgPool, user_id: &i32) -> Result<()> {
+        sqlx::query(
+            r#"
+            delete from
+                users
+            where
+                user_id = $1
+        "#,
+        )
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
     /// searches for users matching the string
     pub async fn search_users(conn: &PgPool, query: &String) -> Result<Vec<UserResponse>> {
         let like_term = format!("%{}%", query);
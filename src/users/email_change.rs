@@ -0,0 +1,70 @@
+use chrono::{Duration, Utc};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use super::token::{generate_opaque_token, hash_opaque_token};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// How long an email change can be confirmed for
+const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+/// Representation of a confirmed email change
+#[derive(FromRow)]
+pub struct EmailChange {
+    pub user_id: i32,
+    pub email: String,
+}
+
+impl EmailChange {
+    /// Creates a pending email change for the user and returns the plain token
+    pub async fn create_email_change(conn: &PgPool, user_id: &i32, email: &str) -> Result<String> {
+        let token = generate_opaque_token();
+
+        sqlx::query(
+            r#"
+            insert into
+                email_changes (token_hash, user_id, email, expires_at)
+            values
+                ($1, $2, $3, $4)
+        "#,
+        )
+        .bind(hash_opaque_token(&token))
+        .bind(user_id)
+        .bind(email)
+        .bind(Utc::now() + Duration::hours(EMAIL_CHANGE_TTL_HOURS))
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(token)
+    }
+
+    /// Marks the token as used and returns the email change. Returns none if the token
+    /// does not exist, has expired or has already been used.
+    pub async fn use_email_change_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        token: &str,
+    ) -> Result<Option<EmailChange>> {
+        sqlx::query_as::<_, EmailChange>(
+            r#"
+            update
+                email_changes
+            set
+                used_at = now()
+            where
+                token_hash = $1
+            and
+                used_at is null
+            and
+                expires_at > now()
+            returning
+                user_id, email
+        "#,
+        )
+        .bind(hash_opaque_token(token))
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
@@ -1,33 +1,59 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use futures::future;
+use itertools::Itertools;
 use jsonwebtoken::TokenData;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use validator::Validate;
+use warp::hyper::StatusCode;
 
 use super::db::{User, UserResponse};
-use super::token::Claims;
+use super::email_change::EmailChange;
+use super::email_verification::EmailVerification;
+use super::password::{hash_password, needs_rehash, verify_password};
+use super::password_reset::PasswordReset;
+use super::session::Session;
+use super::token::{Claims, TokenResponse};
 
 use crate::config::Config;
 use crate::errors::TalliiError;
-use crate::ResponseResult;
+use crate::history::db::ScoreEvent;
+use crate::leagues::db::League;
+use crate::mailer::{Email, Mailer};
+use crate::ratings::db::{Rating, RatingHistory};
+use crate::scoreboards::db::Scoreboard;
+use crate::teams::db::Team;
+use crate::templates::db::Template;
+use crate::{ResponseResult, Result};
 
 //////////////////////////////////////////////////
 /// get my user profile
 //////////////////////////////////////////////////
+#[derive(Serialize)]
+pub struct MeResponse {
+    #[serde(flatten)]
+    user: UserResponse,
+    email_verified_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+}
+
 pub async fn get_me(
     pool: Arc<PgPool>,
     token: TokenData<Claims>,
 ) -> ResponseResult<impl warp::Reply> {
     let user = User::get_by_user_id(&pool, &token.claims.sub).await?;
 
-    let response = UserResponse {
-        user_id: user.user_id,
-        email: user.email,
-        username: user.username,
-        avatar_background: user.avatar_background,
-        avatar_emoji: user.avatar_emoji,
-        created_at: user.created_at,
+    let response = MeResponse {
+        user: UserResponse {
+            user_id: user.user_id,
+            email: user.email,
+            username: user.username,
+            avatar_background: user.avatar_background,
+            avatar_emoji: user.avatar_emoji,
+            created_at: user.created_at,
+        },
+        email_verified_at: user.email_verified_at,
     };
 
     Ok(warp::reply::json(&response))
@@ -51,9 +77,9 @@ pub async fn get_user(
             created_at: user.created_at,
         };
 
-        return Ok(warp::reply::json(&response));
+        Ok(warp::reply::json(&response))
     } else {
-        return Err(warp::reject::not_found());
+        Err(warp::reject::not_found())
     }
 }
 
@@ -71,36 +97,52 @@ pub struct LoginPayload {
 #[derive(Serialize)]
 pub struct LoginResponse {
     access_token: String,
+    refresh_token: String,
     user: UserResponse,
 }
 
-pub async fn login(payload: LoginPayload, pool: Arc<PgPool>) -> ResponseResult<impl warp::Reply> {
+pub async fn login(
+    payload: LoginPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+) -> ResponseResult<impl warp::Reply> {
     // validate the request payload
     payload
         .validate()
         .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
 
     // get the user from the database
-    let user = User::get_by_email_option(&*pool, &payload.email).await?;
+    let user = User::get_by_email_option(&pool, &payload.email).await?;
 
     match user {
         Some(user) => {
             // check to make sure the passwords are the same, if they arent, return an error
-            let password = payload.password.as_bytes();
-            let hash = user.password;
-            let matches = argon2::verify_encoded(&hash, password).unwrap();
-
-            if !matches {
+            if !verify_password(&user.password, &payload.password)? {
                 return Err(warp::reject::custom(TalliiError::Unauthorized));
             }
 
+            // upgrade hashes made with the old global salt or outdated argon2 parameters now
+            // that we have the plain password. failing to do so should not fail the login.
+            if needs_rehash(&user.password, &config) {
+                let hash = hash_password(&payload.password, &config)?;
+
+                if let Err(e) = User::update_password(&pool, &user.user_id, &hash).await {
+                    log::warn!("failed to rehash password of user {}: {}", user.user_id, e);
+                }
+            }
+
+            // start a new session for the user
+            let (session, refresh_token) = Session::create_session(&pool, &user.user_id).await?;
+
             // create a new jwt
-            let access_token = Claims::generate_jwt(&user.email, &user.user_id)
-                .map_err(|e| warp::reject::custom(e))?;
+            let access_token =
+                Claims::generate_jwt(&user.email, &user.user_id, &session.session_id)
+                    .map_err(warp::reject::custom)?;
 
             // create response
             let response = LoginResponse {
                 access_token,
+                refresh_token,
                 user: UserResponse {
                     user_id: user.user_id,
                     username: user.username,
@@ -112,7 +154,7 @@ pub async fn login(payload: LoginPayload, pool: Arc<PgPool>) -> ResponseResult<i
             };
 
             // respond with the access and refresh tokens
-            return Ok(warp::reply::json(&response));
+            Ok(warp::reply::json(&response))
         }
         None => Err(warp::reject::custom(TalliiError::Unauthorized)),
     }
@@ -135,6 +177,7 @@ pub async fn signup(
     payload: SignupPayload,
     pool: Arc<PgPool>,
     config: Config,
+    mailer: Arc<dyn Mailer>,
 ) -> ResponseResult<impl warp::Reply> {
     // validate the request payload
     payload
@@ -142,7 +185,7 @@ pub async fn signup(
         .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
 
     // check if user with email exists
-    let user = User::get_by_email_option(&*pool, &payload.email).await?;
+    let user = User::get_by_email_option(&pool, &payload.email).await?;
 
     // if the user exists, return an error denoting that the email already exists
     if user.is_some() {
@@ -150,24 +193,36 @@ pub async fn signup(
     }
 
     // create the hashed password
-    let argon_config = argon2::Config::default();
-    let hash = argon2::hash_encoded(
-        payload.password.as_bytes(),
-        &config.salt.as_bytes(),
-        &argon_config,
-    )
-    .unwrap();
+    let hash = hash_password(&payload.password, &config)?;
 
     // insert the user
-    let created_user = User::create_user(&*pool, &payload.username, &payload.email, &hash).await?;
+    let created_user = User::create_user(&pool, &payload.username, &payload.email, &hash).await?;
+
+    // send the verification email. the user can ask for another one so failing to send it
+    // should not fail the signup.
+    if let Err(e) = send_verification_email(&pool, &config, &mailer, &created_user).await {
+        log::warn!(
+            "failed to send verification email to user {}: {}",
+            created_user.user_id,
+            e
+        );
+    }
+
+    // start a new session for the user
+    let (session, refresh_token) = Session::create_session(&pool, &created_user.user_id).await?;
 
     // create the access token
-    let access_token = Claims::generate_jwt(&created_user.email, &created_user.user_id)
-        .map_err(|e| warp::reject::custom(e))?;
+    let access_token = Claims::generate_jwt(
+        &created_user.email,
+        &created_user.user_id,
+        &session.session_id,
+    )
+    .map_err(warp::reject::custom)?;
 
     // create response
     let response = LoginResponse {
         access_token,
+        refresh_token,
         user: UserResponse {
             user_id: created_user.user_id,
             username: created_user.username,
@@ -205,14 +260,531 @@ pub async fn update_me(
     )
     .await?;
 
-    let response = UserResponse {
-        user_id: user.user_id,
-        email: user.email,
-        username: user.username,
-        avatar_background: user.avatar_background,
-        avatar_emoji: user.avatar_emoji,
-        created_at: user.created_at,
+    let response = MeResponse {
+        user: UserResponse {
+            user_id: user.user_id,
+            email: user.email,
+            username: user.username,
+            avatar_background: user.avatar_background,
+            avatar_emoji: user.avatar_emoji,
+            created_at: user.created_at,
+        },
+        email_verified_at: user.email_verified_at,
     };
 
     Ok(warp::reply::json(&response))
 }
+
+//////////////////////////////////////////////////
+/// refresh the access token
+//////////////////////////////////////////////////
+#[derive(Deserialize)]
+pub struct RefreshTokenPayload {
+    refresh_token: String,
+}
+
+pub async fn refresh_token(
+    payload: RefreshTokenPayload,
+    pool: Arc<PgPool>,
+) -> ResponseResult<impl warp::Reply> {
+    // rotate the refresh token, if the session is not active the refresh token is rejected
+    let (session, refresh_token) = Session::rotate_refresh_token(&pool, &payload.refresh_token)
+        .await?
+        .ok_or_else(|| warp::reject::custom(TalliiError::InvalidToken))?;
+
+    // get the user of the session
+    let user = User::get_by_user_id(&pool, &session.user_id).await?;
+
+    // create the access token
+    let access_token = Claims::generate_jwt(&user.email, &user.user_id, &session.session_id)
+        .map_err(warp::reject::custom)?;
+
+    let response = TokenResponse {
+        access_token,
+        refresh_token,
+    };
+
+    Ok(warp::reply::json(&response))
+}
+
+//////////////////////////////////////////////////
+/// log user out
+//////////////////////////////////////////////////
+pub async fn logout(
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    Session::revoke_session(&pool, &token.claims.sid).await?;
+
+    Ok(warp::reply::with_status("logged out", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// log user out of every session
+//////////////////////////////////////////////////
+pub async fn logout_all(
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    Session::revoke_user_sessions(&pool, &token.claims.sub).await?;
+
+    Ok(warp::reply::with_status("logged out", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// request a password reset
+//////////////////////////////////////////////////
+#[derive(Deserialize, Validate)]
+pub struct ForgotPasswordPayload {
+    #[validate(email)]
+    email: String,
+}
+
+pub async fn forgot_password(
+    payload: ForgotPasswordPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // only send the email if the user exists, the response is the same either way so that
+    // the endpoint can't be used to find out which emails have an account
+    if let Some(user) = User::get_by_email_option(&pool, &payload.email).await? {
+        let token = PasswordReset::create_password_reset(&pool, &user.user_id).await?;
+
+        // a failing mailer is only logged, answering with an error would reveal the account
+        if let Err(e) = mailer
+            .send(Email {
+                to: user.email,
+                subject: String::from("Reset your tallii password"),
+                body: format!(
+                    "Hi {},\n\nUse the link below to reset your password. It expires in an hour.\n\n{}/reset-password?token={}\n\nIf you didn't ask to reset your password you can ignore this email.",
+                    user.username, config.app_url, token
+                ),
+            })
+            .await
+        {
+            log::warn!(
+                "failed to send password reset email to user {}: {}",
+                user.user_id,
+                e
+            );
+        }
+    }
+
+    Ok(warp::reply::with_status(
+        "password reset requested",
+        StatusCode::OK,
+    ))
+}
+
+//////////////////////////////////////////////////
+/// reset a password
+//////////////////////////////////////////////////
+#[derive(Deserialize, Validate)]
+pub struct ResetPasswordPayload {
+    token: String,
+    #[validate(length(min = 6))]
+    password: String,
+}
+
+pub async fn reset_password(
+    payload: ResetPasswordPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    // create the hashed password
+    let hash = hash_password(&payload.password, &config)?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // use up the token, the password is only changed if the token is valid
+    let user_id = PasswordReset::use_password_reset_tx(&mut tx, &payload.token)
+        .await?
+        .ok_or_else(|| warp::reject::custom(TalliiError::InvalidToken))?;
+
+    // update the password
+    User::update_password_tx(&mut tx, &user_id, &hash).await?;
+
+    // log the user out everywhere since the old password may have been compromised
+    Session::revoke_user_sessions_tx(&mut tx, &user_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status("password reset", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// change the password of the logged in user
+//////////////////////////////////////////////////
+#[derive(Deserialize, Validate)]
+pub struct ChangePasswordPayload {
+    current_password: String,
+    #[validate(length(min = 6))]
+    new_password: String,
+}
+
+pub async fn change_password(
+    payload: ChangePasswordPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    let user = User::get_by_user_id(&pool, &token.claims.sub).await?;
+
+    // the current password has to be provided again to change it
+    if !verify_password(&user.password, &payload.current_password)? {
+        return Err(warp::reject::custom(TalliiError::Unauthorized));
+    }
+
+    let hash = hash_password(&payload.new_password, &config)?;
+
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // update the password
+    User::update_password_tx(&mut tx, &user.user_id, &hash).await?;
+
+    // log the user out everywhere and start a new session for the current device
+    Session::revoke_user_sessions_tx(&mut tx, &user.user_id).await?;
+    let (session, refresh_token) = Session::create_session_tx(&mut tx, &user.user_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    let access_token = Claims::generate_jwt(&user.email, &user.user_id, &session.session_id)
+        .map_err(warp::reject::custom)?;
+
+    let response = TokenResponse {
+        access_token,
+        refresh_token,
+    };
+
+    Ok(warp::reply::json(&response))
+}
+
+//////////////////////////////////////////////////
+/// request to change the email of the logged in user
+//////////////////////////////////////////////////
+#[derive(Deserialize, Validate)]
+pub struct ChangeEmailPayload {
+    #[validate(email)]
+    email: String,
+    password: String,
+}
+
+pub async fn change_email(
+    payload: ChangeEmailPayload,
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // validate the request payload
+    payload
+        .validate()
+        .map_err(|e| warp::reject::custom(TalliiError::ValidationError(e.to_string())))?;
+
+    let user = User::get_by_user_id(&pool, &token.claims.sub).await?;
+
+    // the password has to be provided to change the email
+    if !verify_password(&user.password, &payload.password)? {
+        return Err(warp::reject::custom(TalliiError::Unauthorized));
+    }
+
+    // if a user with the email exists, return an error denoting that the email already exists
+    if User::get_by_email_option(&pool, &payload.email)
+        .await?
+        .is_some()
+    {
+        return Err(warp::reject::custom(TalliiError::UserEmailTaken));
+    }
+
+    // the email is only changed once the new address is confirmed
+    let token = EmailChange::create_email_change(&pool, &user.user_id, &payload.email).await?;
+
+    mailer
+        .send(Email {
+            to: payload.email,
+            subject: String::from("Confirm your new tallii email"),
+            body: format!(
+                "Hi {},\n\nUse the link below to confirm your new email. It expires in a day.\n\n{}/confirm-email?token={}\n\nIf you didn't ask to change your email you can ignore this email.",
+                user.username, config.app_url, token
+            ),
+        })
+        .await?;
+
+    Ok(warp::reply::with_status(
+        "email change requested",
+        StatusCode::ACCEPTED,
+    ))
+}
+
+//////////////////////////////////////////////////
+/// confirm an email change
+//////////////////////////////////////////////////
+#[derive(Deserialize)]
+pub struct ConfirmEmailChangePayload {
+    token: String,
+}
+
+pub async fn confirm_email_change(
+    payload: ConfirmEmailChangePayload,
+    pool: Arc<PgPool>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // use up the token, the email is only changed if the token is valid
+    let email_change = EmailChange::use_email_change_tx(&mut tx, &payload.token)
+        .await?
+        .ok_or_else(|| warp::reject::custom(TalliiError::InvalidToken))?;
+
+    // update the email
+    User::update_email_tx(&mut tx, &email_change.user_id, &email_change.email).await?;
+
+    // the email is part of the access token so every session has to log in again
+    Session::revoke_user_sessions_tx(&mut tx, &email_change.user_id).await?;
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status("email changed", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// verify the email of a user
+//////////////////////////////////////////////////
+#[derive(Deserialize)]
+pub struct VerifyEmailPayload {
+    token: String,
+}
+
+pub async fn verify_email(
+    payload: VerifyEmailPayload,
+    pool: Arc<PgPool>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the transaction
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    // use up the token
+    let email_verification = EmailVerification::use_email_verification_tx(&mut tx, &payload.token)
+        .await?
+        .ok_or_else(|| warp::reject::custom(TalliiError::InvalidToken))?;
+
+    // the token is only valid if the user still has the email it was sent to
+    let verified = User::verify_email_tx(
+        &mut tx,
+        &email_verification.user_id,
+        &email_verification.email,
+    )
+    .await?;
+
+    if !verified {
+        return Err(warp::reject::custom(TalliiError::InvalidToken));
+    }
+
+    // commit the transaction
+    tx.commit()
+        .await
+        .map_err(|_err| warp::reject::custom(TalliiError::SQLXError))?;
+
+    Ok(warp::reply::with_status("email verified", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// resend the verification email
+//////////////////////////////////////////////////
+pub async fn resend_verification_email(
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let user = User::get_by_user_id(&pool, &token.claims.sub).await?;
+
+    if user.email_verified_at.is_some() {
+        return Err(warp::reject::custom(TalliiError::BadRequest(String::from(
+            "email already verified",
+        ))));
+    }
+
+    send_verification_email(&pool, &config, &mailer, &user).await?;
+
+    Ok(warp::reply::with_status(
+        "verification email sent",
+        StatusCode::OK,
+    ))
+}
+
+/// creates an email verification for the user and emails it to them
+async fn send_verification_email(
+    pool: &PgPool,
+    config: &Config,
+    mailer: &Arc<dyn Mailer>,
+    user: &User,
+) -> Result<()> {
+    let token =
+        EmailVerification::create_email_verification(pool, &user.user_id, &user.email).await?;
+
+    mailer
+        .send(Email {
+            to: user.email.clone(),
+            subject: String::from("Verify your tallii email"),
+            body: format!(
+                "Hi {},\n\nUse the link below to verify your email. It expires in three days.\n\n{}/verify-email?token={}",
+                user.username, config.app_url, token
+            ),
+        })
+        .await
+}
+
+//////////////////////////////////////////////////
+/// delete the logged in user
+//////////////////////////////////////////////////
+#[derive(Deserialize)]
+pub struct DeleteMePayload {
+    password: String,
+}
+
+pub async fn delete_me(
+    payload: DeleteMePayload,
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    let user = User::get_by_user_id(&pool, &token.claims.sub).await?;
+
+    // the password has to be provided to delete the account
+    if !verify_password(&user.password, &payload.password)? {
+        return Err(warp::reject::custom(TalliiError::Unauthorized));
+    }
+
+    // deleting the user also deletes their sessions, scoreboards and teams
+    User::delete_user(&pool, &user.user_id).await?;
+
+    Ok(warp::reply::with_status("user deleted", StatusCode::OK))
+}
+
+//////////////////////////////////////////////////
+/// export the data of the logged in user
+//////////////////////////////////////////////////
+#[derive(Serialize)]
+pub struct ScoreboardExport {
+    #[serde(flatten)]
+    scoreboard: Scoreboard,
+    teams: Vec<Team>,
+}
+
+#[derive(Serialize)]
+pub struct UserExport {
+    exported_at: chrono::DateTime<chrono::offset::Utc>,
+    user: MeResponse,
+    scoreboards: Vec<ScoreboardExport>,
+    score_events: Vec<ScoreEvent>,
+    played_teams: Vec<Team>,
+    ratings: Vec<Rating>,
+    rating_history: Vec<RatingHistory>,
+    templates: Vec<Template>,
+    leagues: Vec<League>,
+}
+
+pub async fn export_me(
+    pool: Arc<PgPool>,
+    token: TokenData<Claims>,
+) -> ResponseResult<impl warp::Reply> {
+    // get the user, their scoreboards, the teams of those scoreboards and the score changes they
+    // made in parallel
+    let (user, scoreboards, teams, score_events) = future::try_join4(
+        User::get_by_user_id(&pool, &token.claims.sub),
+        Scoreboard::get_scoreboards_by_user_id(&pool, &token.claims.sub),
+        Team::get_teams_by_scoreboard_created_by(&pool, &token.claims.sub),
+        ScoreEvent::get_score_events_by_user_id(&pool, &token.claims.sub),
+    )
+    .await?;
+
+    // get the teams they played on, their ratings, templates and leagues in parallel
+    let (played_teams, ratings, rating_history, templates, leagues) = future::try_join5(
+        Team::get_teams_by_player(&pool, &token.claims.sub),
+        Rating::get_ratings_by_user_id(&pool, &token.claims.sub),
+        RatingHistory::get_rating_history_by_user_id(&pool, &token.claims.sub),
+        Template::get_templates_by_user_id(&pool, &token.claims.sub),
+        League::get_leagues_by_member(&pool, &token.claims.sub),
+    )
+    .await?;
+
+    // group the teams by their scoreboard
+    let mut grouped_teams: HashMap<i32, Vec<Team>> = HashMap::new();
+    for (scoreboard_id, teams) in &teams.into_iter().group_by(|team| team.scoreboard_id) {
+        grouped_teams.insert(scoreboard_id, teams.collect::<Vec<Team>>());
+    }
+
+    let response = UserExport {
+        exported_at: chrono::Utc::now(),
+        user: MeResponse {
+            user: UserResponse {
+                user_id: user.user_id,
+                email: user.email,
+                username: user.username,
+                avatar_background: user.avatar_background,
+                avatar_emoji: user.avatar_emoji,
+                created_at: user.created_at,
+            },
+            email_verified_at: user.email_verified_at,
+        },
+        scoreboards: scoreboards
+            .into_iter()
+            .map(|scoreboard| ScoreboardExport {
+                teams: grouped_teams
+                    .remove(&scoreboard.scoreboard_id)
+                    .unwrap_or_default(),
+                scoreboard,
+            })
+            .collect(),
+        score_events,
+        played_teams,
+        ratings,
+        rating_history,
+        templates,
+        leagues,
+    };
+
+    Ok(warp::reply::with_header(
+        warp::reply::json(&response),
+        "content-disposition",
+        "attachment; filename=\"tallii-export.json\"",
+    ))
+}
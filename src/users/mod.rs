@@ -1,4 +1,9 @@
 pub mod db;
+pub mod email_change;
+pub mod email_verification;
 pub mod handlers;
+pub mod password;
+pub mod password_reset;
 pub mod routes;
+pub mod session;
 pub mod token;
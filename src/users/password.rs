@@ -0,0 +1,58 @@
+use rand::RngCore;
+
+use crate::config::Config;
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// Hashes the password with argon2 using a freshly generated salt
+pub fn hash_password(password: &str, config: &Config) -> Result<String> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    argon2::hash_encoded(password.as_bytes(), &salt, &argon_config(config)?)
+        .map_err(|e| TalliiError::InternalServerError(e.to_string()))
+}
+
+/// Checks if the password matches the hash
+pub fn verify_password(hash: &str, password: &str) -> Result<bool> {
+    argon2::verify_encoded(hash, password.as_bytes())
+        .map_err(|e| TalliiError::InternalServerError(e.to_string()))
+}
+
+/// Checks if the hash was created with the old global salt or with argon2 parameters that
+/// differ from the ones in the config
+pub fn needs_rehash(hash: &str, config: &Config) -> bool {
+    let argon_config = match argon_config(config) {
+        Ok(argon_config) => argon_config,
+        Err(_) => return false,
+    };
+
+    // an encoded hash looks like $argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>
+    let parts: Vec<&str> = hash.split('$').collect();
+    if parts.len() != 6 {
+        return true;
+    }
+
+    let params = format!(
+        "m={},t={},p={}",
+        argon_config.mem_cost, argon_config.time_cost, argon_config.lanes
+    );
+    let legacy_salt = base64::encode_config(config.salt.as_bytes(), base64::STANDARD_NO_PAD);
+
+    parts[1] != argon_config.variant.as_lowercase_str()
+        || parts[3] != params
+        || parts[4] == legacy_salt
+}
+
+/// Creates the argon2 config from the app config
+fn argon_config(config: &Config) -> Result<argon2::Config<'static>> {
+    let variant = argon2::Variant::from_str(&config.argon2_variant)
+        .map_err(|e| TalliiError::InternalServerError(e.to_string()))?;
+
+    Ok(argon2::Config {
+        mem_cost: config.argon2_memory_cost,
+        time_cost: config.argon2_iterations,
+        variant,
+        ..argon2::Config::default()
+    })
+}
@@ -0,0 +1,64 @@
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use super::token::{generate_opaque_token, hash_opaque_token};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// How long a password reset token can be used for
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+pub struct PasswordReset;
+
+impl PasswordReset {
+    /// Creates a password reset for the user and returns the plain token
+    pub async fn create_password_reset(conn: &PgPool, user_id: &i32) -> Result<String> {
+        let token = generate_opaque_token();
+
+        sqlx::query(
+            r#"
+            insert into
+                password_resets (token_hash, user_id, expires_at)
+            values
+                ($1, $2, $3)
+        "#,
+        )
+        .bind(hash_opaque_token(&token))
+        .bind(user_id)
+        .bind(Utc::now() + Duration::minutes(PASSWORD_RESET_TTL_MINUTES))
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(token)
+    }
+
+    /// Marks the token as used and returns the user it belongs to. Returns none if the
+    /// token does not exist, has expired or has already been used.
+    pub async fn use_password_reset_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        token: &str,
+    ) -> Result<Option<i32>> {
+        sqlx::query_scalar::<_, i32>(
+            r#"
+            update
+                password_resets
+            set
+                used_at = now()
+            where
+                token_hash = $1
+            and
+                used_at is null
+            and
+                expires_at > now()
+            returning
+                user_id
+        "#,
+        )
+        .bind(hash_opaque_token(token))
+        .fetch_optional(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+}
@@ -7,7 +7,8 @@ use warp::Filter;
 use super::handlers;
 use super::token::Claims;
 use crate::config::Config;
-use crate::wrappers::{with_auth, with_config, with_pool};
+use crate::mailer::Mailer;
+use crate::wrappers::{with_auth, with_config, with_mailer, with_pool};
 
 pub struct AuthRoutes;
 
@@ -16,22 +17,43 @@ impl AuthRoutes {
     pub fn init(
         pool: Arc<PgPool>,
         config: Config,
+        mailer: Arc<dyn Mailer>,
     ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-        let auth_routes = authorize().or(login(pool.clone())
-            .or(signup(pool.clone(), config.clone()))
+        authorize(pool.clone()).or(login(pool.clone(), config.clone())
+            .or(signup(pool.clone(), config.clone(), mailer.clone()))
+            .or(verify_email(pool.clone()))
+            .or(resend_verification_email(
+                pool.clone(),
+                config.clone(),
+                mailer.clone(),
+            ))
+            .or(refresh_token(pool.clone()))
+            .or(logout(pool.clone()))
+            .or(logout_all(pool.clone()))
+            .or(forgot_password(
+                pool.clone(),
+                config.clone(),
+                mailer.clone(),
+            ))
+            .or(reset_password(pool.clone(), config.clone()))
             .or(get_me(pool.clone()))
             .or(update_me(pool.clone()))
-            .or(get_user(pool.clone())));
-
-        auth_routes
+            .or(delete_me(pool.clone()))
+            .or(export_me(pool.clone()))
+            .or(change_password(pool.clone(), config.clone()))
+            .or(change_email(pool.clone(), config.clone(), mailer.clone()))
+            .or(confirm_email_change(pool.clone()))
+            .or(get_user(pool.clone())))
     }
 }
 
 /// GET /v1/authorize - Validates a token
-pub fn authorize() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+pub fn authorize(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "authorize")
         .and(warp::get())
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .map(move |_: TokenData<Claims>| warp::reply())
 }
 
@@ -42,7 +64,7 @@ pub fn get_me(
     warp::path!("v1" / "me")
         .and(warp::get())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::get_me)
 }
 
@@ -53,7 +75,7 @@ pub fn get_user(
     warp::path!("v1" / "users" / i32)
         .and(warp::get())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::get_user)
 }
 
@@ -65,18 +87,84 @@ pub fn update_me(
         .and(warp::put())
         .and(warp::body::json())
         .and(with_pool(pool.clone()))
-        .and(with_auth())
+        .and(with_auth(pool.clone()))
         .and_then(handlers::update_me)
 }
 
+/// DELETE /v1/me - deletes the currently logged in user
+pub fn delete_me(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me")
+        .and(warp::delete())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::delete_me)
+}
+
+/// GET /v1/me/export - exports all of the data of the currently logged in user
+pub fn export_me(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "export")
+        .and(warp::get())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::export_me)
+}
+
+/// PUT /v1/me/password - changes the password of the currently logged in user
+pub fn change_password(
+    pool: Arc<PgPool>,
+    config: Config,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "password")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::change_password)
+}
+
+/// PUT /v1/me/email - sends a confirmation to the new email of the currently logged in user
+pub fn change_email(
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "email")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_mailer(mailer.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::change_email)
+}
+
+/// POST /v1/me/email/confirm - applies an email change with the token sent to the new email
+pub fn confirm_email_change(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "me" / "email" / "confirm")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and_then(handlers::confirm_email_change)
+}
+
 /// Logs a user into the applicaton
 pub fn login(
     pool: Arc<PgPool>,
+    config: Config,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "login")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
         .and_then(handlers::login)
 }
 
@@ -84,11 +172,100 @@ pub fn login(
 pub fn signup(
     pool: Arc<PgPool>,
     config: Config,
+    mailer: Arc<dyn Mailer>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!("v1" / "signup")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_pool(pool.clone()))
         .and(with_config(config.clone()))
+        .and(with_mailer(mailer.clone()))
         .and_then(handlers::signup)
 }
+
+/// POST /v1/verify-email - verifies the email of a user with the token sent at signup
+pub fn verify_email(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "verify-email")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and_then(handlers::verify_email)
+}
+
+/// POST /v1/verify-email/resend - sends another verification email to the logged in user
+pub fn resend_verification_email(
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "verify-email" / "resend")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_mailer(mailer.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::resend_verification_email)
+}
+
+/// POST /v1/token/refresh - exchanges a refresh token for a new access and refresh token
+pub fn refresh_token(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "token" / "refresh")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and_then(handlers::refresh_token)
+}
+
+/// POST /v1/logout - revokes the session of the current access token
+pub fn logout(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "logout")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::logout)
+}
+
+/// POST /v1/logout/all - revokes every session of the currently logged in user
+pub fn logout_all(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "logout" / "all")
+        .and(warp::post())
+        .and(with_pool(pool.clone()))
+        .and(with_auth(pool.clone()))
+        .and_then(handlers::logout_all)
+}
+
+/// POST /v1/password/forgot - emails the user a link to reset their password
+pub fn forgot_password(
+    pool: Arc<PgPool>,
+    config: Config,
+    mailer: Arc<dyn Mailer>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "password" / "forgot")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and(with_mailer(mailer.clone()))
+        .and_then(handlers::forgot_password)
+}
+
+/// POST /v1/password/reset - resets the password of a user with a reset token
+pub fn reset_password(
+    pool: Arc<PgPool>,
+    config: Config,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("v1" / "password" / "reset")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_pool(pool.clone()))
+        .and(with_config(config.clone()))
+        .and_then(handlers::reset_password)
+}
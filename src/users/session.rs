@@ -0,0 +1,206 @@
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use super::token::{generate_opaque_token, hash_opaque_token};
+
+use crate::errors::TalliiError;
+use crate::Result;
+
+/// How long a refresh token can be used before the user has to log in again
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Representation of a session in the database
+#[derive(sqlx::FromRow)]
+pub struct Session {
+    pub session_id: Uuid,
+    pub user_id: i32,
+    pub refresh_token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::offset::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::offset::Utc>>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+impl Session {
+    /// Creates a session for the user and returns it along with the plain refresh token
+    pub async fn create_session(conn: &PgPool, user_id: &i32) -> Result<(Session, String)> {
+        let refresh_token = generate_opaque_token();
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            insert into
+                sessions (session_id, user_id, refresh_token_hash, expires_at)
+            values
+                ($1, $2, $3, $4)
+            returning
+                *
+        "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_opaque_token(&refresh_token))
+        .bind(Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS))
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok((session, refresh_token))
+    }
+
+    /// Creates a session for the user in a transaction
+    pub async fn create_session_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &i32,
+    ) -> Result<(Session, String)> {
+        let refresh_token = generate_opaque_token();
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            insert into
+                sessions (session_id, user_id, refresh_token_hash, expires_at)
+            values
+                ($1, $2, $3, $4)
+            returning
+                *
+        "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_opaque_token(&refresh_token))
+        .bind(Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS))
+        .fetch_one(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok((session, refresh_token))
+    }
+
+    /// Swaps the provided refresh token for a new one. Returns none if the token does not
+    /// belong to an active session. The swap happens in a single statement so a refresh
+    /// token can only ever be used once.
+    pub async fn rotate_refresh_token(
+        conn: &PgPool,
+        refresh_token: &str,
+    ) -> Result<Option<(Session, String)>> {
+        let new_refresh_token = generate_opaque_token();
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            update
+                sessions
+            set
+                refresh_token_hash = $1,
+                expires_at = $2
+            where
+                refresh_token_hash = $3
+            and
+                revoked_at is null
+            and
+                expires_at > now()
+            returning
+                *
+        "#,
+        )
+        .bind(hash_opaque_token(&new_refresh_token))
+        .bind(Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS))
+        .bind(hash_opaque_token(refresh_token))
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(session.map(|session| (session, new_refresh_token)))
+    }
+
+    /// Checks if the session has not been revoked and has not expired
+    pub async fn is_active(conn: &PgPool, session_id: &Uuid) -> Result<bool> {
+        sqlx::query_scalar::<_, bool>(
+            r#"
+            select exists (
+                select
+                    1
+                from
+                    sessions
+                where
+                    session_id = $1
+                and
+                    revoked_at is null
+                and
+                    expires_at > now()
+            )
+        "#,
+        )
+        .bind(session_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))
+    }
+
+    /// Revokes a single session
+    pub async fn revoke_session(conn: &PgPool, session_id: &Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                sessions
+            set
+                revoked_at = now()
+            where
+                session_id = $1
+            and
+                revoked_at is null
+        "#,
+        )
+        .bind(session_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Revokes every session that belongs to the user
+    pub async fn revoke_user_sessions(conn: &PgPool, user_id: &i32) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                sessions
+            set
+                revoked_at = now()
+            where
+                user_id = $1
+            and
+                revoked_at is null
+        "#,
+        )
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Revokes every session that belongs to the user in a transaction
+    pub async fn revoke_user_sessions_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: &i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            update
+                sessions
+            set
+                revoked_at = now()
+            where
+                user_id = $1
+            and
+                revoked_at is null
+        "#,
+        )
+        .bind(user_id)
+        .execute(tx)
+        .await
+        .map_err(|e| TalliiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -1,15 +1,22 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
 use crate::errors::TalliiError;
 use crate::Result;
 
+/// How long an access token is valid for before it has to be refreshed
+const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+
 /// Represents the contents of a jwt
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32,
     pub email: String,
+    pub sid: Uuid,
     pub exp: i64,
 }
 
@@ -21,7 +28,7 @@ impl Claims {
 
         match decode::<Claims>(
             &token,
-            &DecodingKey::from_secret(&secret.as_bytes()),
+            &DecodingKey::from_secret(secret.as_bytes()),
             &Validation::default(),
         ) {
             Ok(claims) => Ok(claims),
@@ -30,17 +37,18 @@ impl Claims {
     }
 
     /// Encodes the provided token struct to a string
-    pub fn generate_jwt(email: &str, user_id: &i32) -> Result<String> {
+    pub fn generate_jwt(email: &str, user_id: &i32, session_id: &Uuid) -> Result<String> {
         // get secret from env
         let secret = std::env::var("JWT_SECRET").expect("JWT_SECRET not set");
 
-        // expire the token in 7 days
-        let now = Utc::now() + Duration::days(30);
+        // expire the token shortly, the client is expected to refresh it
+        let now = Utc::now() + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
 
         // create the claims
         let claims = Claims {
-            sub: user_id.clone(),
+            sub: *user_id,
             email: email.to_string(),
+            sid: *session_id,
             exp: now.timestamp(),
         };
 
@@ -48,7 +56,7 @@ impl Claims {
         match encode(
             &Header::default(),
             &claims,
-            &EncodingKey::from_secret(&secret.as_bytes()),
+            &EncodingKey::from_secret(secret.as_bytes()),
         ) {
             Ok(token) => Ok(token),
             Err(e) => Err(TalliiError::InternalServerError(e.to_string())),
@@ -59,4 +67,17 @@ impl Claims {
 #[derive(Serialize)]
 pub struct TokenResponse {
     pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// Generates a random opaque token for things like refresh and password reset tokens
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hashes an opaque token so that only the hash is stored in the database
+pub fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
 }
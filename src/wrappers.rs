@@ -1,18 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use jsonwebtoken::TokenData;
+use serde::de::DeserializeOwned;
 use sqlx::PgPool;
+use warp::hyper::body::Bytes;
 use warp::Filter;
 
 use crate::config::Config;
+use crate::live::events::EventSender;
+use crate::mailer::Mailer;
+use crate::users::session::Session;
 use crate::users::token::Claims;
 
 use crate::errors::TalliiError;
 use crate::ResponseResult;
 
-/// Extracts jwt from request in the Authorization header and verfies it
-pub fn with_auth() -> impl Filter<Extract = (TokenData<Claims>,), Error = warp::Rejection> + Clone {
-    warp::header::headers_cloned().and_then(validate_jwt)
+/// Extracts jwt from request in the Authorization header and verfies it along with its session
+pub fn with_auth(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = (TokenData<Claims>,), Error = warp::Rejection> + Clone {
+    warp::header::headers_cloned()
+        .and(with_pool(pool))
+        .and_then(validate_jwt)
+}
+
+/// Extracts jwt from the access_token query param and verifies it along with its session. Meant
+/// for websockets since browsers can't set the Authorization header on them.
+pub fn with_query_auth(
+    pool: Arc<PgPool>,
+) -> impl Filter<Extract = (TokenData<Claims>,), Error = warp::Rejection> + Clone {
+    warp::query::<HashMap<String, String>>()
+        .or(warp::any().map(HashMap::new))
+        .unify()
+        .and(with_pool(pool))
+        .and_then(validate_query_jwt)
 }
 
 /// Extracts claims from request in the Authorization header
@@ -35,26 +57,75 @@ pub fn with_config(
     warp::any().map(move || config.clone())
 }
 
+/// Extracts mailer
+pub fn with_mailer(
+    mailer: Arc<dyn Mailer>,
+) -> impl Filter<Extract = (Arc<dyn Mailer>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || mailer.clone())
+}
+
+/// Extracts the scoreboard event sender
+pub fn with_events(
+    events: EventSender,
+) -> impl Filter<Extract = (EventSender,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || events.clone())
+}
+
+/// Extracts a json merge patch body. warp::body::json only accepts application/json, while
+/// merge patches are usually sent as application/merge-patch+json, so the content type is not
+/// checked.
+pub fn with_merge_patch<T: DeserializeOwned + Send>(
+) -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone {
+    warp::body::bytes().and_then(|body: Bytes| async move {
+        serde_json::from_slice::<T>(&body)
+            .map_err(|e| warp::reject::custom(TalliiError::BadRequest(e.to_string())))
+    })
+}
+
 /// Validates the jwt token
 async fn decode_jwt(
     headers: warp::http::HeaderMap<warp::http::HeaderValue>,
 ) -> ResponseResult<TokenData<Claims>> {
     match jwt_from_headers(&headers) {
-        Ok(token) => Claims::verify_jwt(token.to_string()).map_err(|e| warp::reject::custom(e)),
+        Ok(token) => Claims::verify_jwt(token.to_string()).map_err(warp::reject::custom),
         Err(_) => Err(warp::reject::custom(TalliiError::MissingBearerToken)),
     }
 }
 
-/// Validates the jwt token
+/// Validates the jwt token and makes sure its session has not been revoked
 async fn validate_jwt(
     headers: warp::http::HeaderMap<warp::http::HeaderValue>,
+    pool: Arc<PgPool>,
 ) -> ResponseResult<TokenData<Claims>> {
     match jwt_from_headers(&headers) {
-        Ok(token) => Claims::verify_jwt(token.to_string()).map_err(|e| warp::reject::custom(e)),
+        Ok(token) => verify_session_jwt(token, &pool).await,
         Err(_) => Err(warp::reject::custom(TalliiError::MissingBearerToken)),
     }
 }
 
+/// Validates the jwt token from the query params and makes sure its session has not been revoked
+async fn validate_query_jwt(
+    params: HashMap<String, String>,
+    pool: Arc<PgPool>,
+) -> ResponseResult<TokenData<Claims>> {
+    match params.get("access_token") {
+        Some(token) => verify_session_jwt(token, &pool).await,
+        None => Err(warp::reject::custom(TalliiError::MissingBearerToken)),
+    }
+}
+
+/// Verifies the jwt token and its session
+async fn verify_session_jwt(token: &str, pool: &PgPool) -> ResponseResult<TokenData<Claims>> {
+    let token = Claims::verify_jwt(token.to_string()).map_err(warp::reject::custom)?;
+
+    // a token whose session was revoked or expired is no longer valid
+    if !Session::is_active(pool, &token.claims.sid).await? {
+        return Err(warp::reject::custom(TalliiError::InvalidToken));
+    }
+
+    Ok(token)
+}
+
 /// Get the jwt token from the headers
 fn jwt_from_headers(headers: &warp::http::HeaderMap<warp::http::HeaderValue>) -> Result<&str, ()> {
     // get the authorization header